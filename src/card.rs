@@ -1,7 +1,8 @@
 use frame_support::{
-    codec::{Decode, Encode},
+    codec::{self, Decode, Encode, Input},
     sp_runtime::RuntimeDebug,
 };
+use sp_std::ops::{BitAnd, BitOr};
 use fixed_hash::construct_fixed_hash;
 use sp_std::vec::Vec;
 #[cfg(feature = "std")]
@@ -16,24 +17,112 @@ construct_fixed_hash! {
 
 pub type CardId = u32;
 
+/// Card design, the pallet limits the length of its text.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, Default, RuntimeDebug, PartialEq)]
 pub struct Card {
     pub name: Vec<u8>,
-    pub card_type: CardType,
+    pub mana_cost: ManaCost,
+    pub type_line: TypeLine,
+    pub rarity: Rarity,
     pub color: Color,
     pub rules: Vec<u8>,
+    pub keywords: Keywords,
+    /// Only creatures have power and toughness
+    pub power_toughness: Option<PowerToughness>,
+    /// Only planeswalkers have loyalty
+    pub loyalty: Option<u8>,
     pub image: H256,
 }
 
-pub type Color = u8;
+/// Set of the colors of a card, encoded as a `u8` bitmask.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Clone, Copy, Default, RuntimeDebug, PartialEq, Eq)]
+pub struct Color(u8);
+
+pub const WHITE: Color = Color(1);
+pub const BLACK: Color = Color(2);
+pub const RED: Color = Color(4);
+pub const GREEN: Color = Color(8);
+pub const BLUE: Color = Color(16);
+pub const COLORLESS: Color = Color(0);
+
+/// How many colors a card has.
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum ColorClass {
+    Colorless,
+    Mono,
+    Multicolor,
+}
+
+impl Color {
+    const ALL: u8 = 31;
+
+    /// Color set of a bitmask, `None` if it has undefined bits.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL == 0 { Some(Color(bits)) } else { None }
+    }
+
+    /// Color set of a bitmask, dropping its undefined bits.
+    pub fn from_bits_truncate(bits: u8) -> Self {
+        Color(bits & Self::ALL)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_valid(self) -> bool {
+        self.0 & !Self::ALL == 0
+    }
+
+    pub fn union(self, other: Color) -> Color {
+        Color(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Color) -> Color {
+        Color(self.0 & other.0)
+    }
+
+    /// Whether all colors of `other` are in this set.
+    pub fn contains(self, other: Color) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn class(self) -> ColorClass {
+        match self.count() {
+            0 => ColorClass::Colorless,
+            1 => ColorClass::Mono,
+            _ => ColorClass::Multicolor,
+        }
+    }
+}
+
+impl BitOr for Color {
+    type Output = Color;
+
+    fn bitor(self, other: Color) -> Color {
+        self.union(other)
+    }
+}
+
+impl BitAnd for Color {
+    type Output = Color;
+
+    fn bitand(self, other: Color) -> Color {
+        self.intersection(other)
+    }
+}
 
-pub const WHITE: Color = 1;
-pub const BLACK: Color = 2;
-pub const RED: Color = 4;
-pub const GREEN: Color = 8;
-pub const BLUE: Color = 16;
-pub const COLORLESS: Color = 0;
+impl Decode for Color {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Color::from_bits(u8::decode(input)?).ok_or_else(|| "Undefined color bits".into())
+    }
+}
 
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Clone, Debug, PartialEq)]
@@ -42,9 +131,214 @@ pub enum CardType {
     Instant,
     Creature,
     Aura,
-    Land
+    Land,
+    Artifact,
+    Enchantment,
+    Planeswalker,
 }
 
 impl Default for CardType {
     fn default() -> Self {CardType::Sorcery}
-}
\ No newline at end of file
+}
+
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub enum Supertype {
+    Legendary,
+    Basic,
+    Snow,
+}
+
+/// Type line of a card, e.g. "Legendary Artifact Creature - Elf Warrior".
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, PartialEq)]
+pub struct TypeLine {
+    pub supertypes: Vec<Supertype>,
+    /// At least one type, a card may have several
+    pub types: Vec<CardType>,
+    /// Words like "Elf", "Warrior" or "Forest"
+    pub subtypes: Vec<Vec<u8>>,
+}
+/// Colored symbol in a mana cost.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum ManaPip {
+    /// Mana of a single color, e.g. {G}
+    Colored(Color),
+    /// Mana of either of two colors, e.g. {R/G}
+    Hybrid(Color, Color),
+}
+
+impl ManaPip {
+    pub fn colors(self) -> Color {
+        match self {
+            ManaPip::Colored(color) => color,
+            ManaPip::Hybrid(first, second) => first | second,
+        }
+    }
+
+    /// Whether the pip names one color, or two different ones if it is hybrid.
+    pub fn is_valid(self) -> bool {
+        match self {
+            ManaPip::Colored(color) => color.count() == 1,
+            ManaPip::Hybrid(first, second) => first.count() == 1 && second.count() == 1 && first != second,
+        }
+    }
+}
+
+/// Mana cost of a card, e.g. {X}{2}{R}{R/G}.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, PartialEq)]
+pub struct ManaCost {
+    /// Number of {X} symbols
+    pub x: u8,
+    /// Mana of any type
+    pub generic: u8,
+    pub pips: Vec<ManaPip>,
+}
+
+impl ManaCost {
+    /// Total mana of the cost, {X} counts as zero.
+    pub fn mana_value(&self) -> u32 {
+        u32::from(self.generic) + self.pips.len() as u32
+    }
+
+    /// Colors of the pips of the cost.
+    pub fn color_identity(&self) -> Color {
+        self.pips.iter().fold(COLORLESS, |colors, pip| colors | pip.colors())
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, Default, RuntimeDebug, PartialEq, Eq)]
+pub struct PowerToughness {
+    pub power: u8,
+    pub toughness: u8,
+}
+
+#[derive(Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum Keyword {
+    Flying,
+    Trample,
+    Haste,
+    FirstStrike,
+    DoubleStrike,
+    Deathtouch,
+    Lifelink,
+    Vigilance,
+    Reach,
+    Menace,
+    Defender,
+    Hexproof,
+    Indestructible,
+    Flash,
+}
+
+impl Keyword {
+    const COUNT: u32 = 14;
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// Set of keyword abilities of a card, encoded as a `u32` bitmask of `Keyword`s.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Clone, Copy, Default, RuntimeDebug, PartialEq, Eq)]
+pub struct Keywords(u32);
+
+impl Keywords {
+    /// Keyword set of a bitmask, `None` if it has undefined bits.
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        if bits >> Keyword::COUNT == 0 { Some(Keywords(bits)) } else { None }
+    }
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn with(self, keyword: Keyword) -> Self {
+        Keywords(self.0 | keyword.bit())
+    }
+
+    pub fn contains(self, keyword: Keyword) -> bool {
+        self.0 & keyword.bit() != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<&[Keyword]> for Keywords {
+    fn from(keywords: &[Keyword]) -> Self {
+        keywords.iter().fold(Keywords::default(), |set, keyword| set.with(*keyword))
+    }
+}
+
+impl Decode for Keywords {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        Keywords::from_bits(u32::decode(input)?).ok_or_else(|| "Undefined keyword bits".into())
+    }
+}
+
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Mythic,
+    Promo,
+}
+
+impl Default for Rarity {
+    fn default() -> Self {Rarity::Common}
+}
+
+pub type SetId = u32;
+
+/// Expansion cards are created into, e.g. "DOM" Dominaria.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct CardSet<AccountId, BlockNumber> {
+    pub creator: AccountId,
+    /// Short unique code, e.g. "DOM"
+    pub code: Vec<u8>,
+    pub name: Vec<u8>,
+    /// Block the set is released in
+    pub release: BlockNumber,
+    /// Highest collector number of the set
+    pub size: u32,
+    /// Whether cards can not be added anymore
+    pub closed: bool,
+}
+
+/// Rules and stats of a card before an erratum replaced them.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct Erratum<BlockNumber> {
+    pub rules: Vec<u8>,
+    pub keywords: Keywords,
+    pub power_toughness: Option<PowerToughness>,
+    pub loyalty: Option<u8>,
+    /// Block in which these were replaced
+    pub replaced: BlockNumber,
+}
+
+/// Who designed a card and when.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct CardInfo<AccountId, BlockNumber, Moment> {
+    pub creator: AccountId,
+    /// Block the card was created in
+    pub created: BlockNumber,
+    /// Time the card was created at
+    pub timestamp: Moment,
+    /// Copies created with the card
+    pub initial_supply: u16,
+}
+
+/// Copies of a card minted so far and the most that may ever be minted.
+#[derive(Encode, Decode, Clone, Default, RuntimeDebug, PartialEq, Eq)]
+pub struct Supply {
+    pub minted: u32,
+    pub max: Option<u32>,
+}
@@ -2,6 +2,8 @@
 
 pub use pallet::*;
 pub mod card;
+pub mod market;
+pub mod migration;
 
 #[cfg(test)]
 pub mod mock;
@@ -13,20 +15,94 @@ mod test_encode;
 #[frame_support::pallet]
 pub mod pallet {
     use crate::card::CardId;
-    use crate::card::Card;
+    use crate::card::{
+        Card, CardInfo, CardSet, CardType, Erratum, Keywords, PowerToughness, Rarity, SetId, Supply, COLORLESS,
+    };
+    use crate::card::{ManaCost, TypeLine};
+    use crate::migration::{self, Releases};
+    use crate::market::{
+        AssetListing, AssetTransfer, Auction, AuctionId, DutchOffer, MarketStats, Order, OrderId, Rental, RentalId, RentalOffer,
+        SaleRecord, SealedAuction, SealedBid, Side, TradeId, TradeOffer,
+    };
     use frame_support::{
 		dispatch::DispatchResultWithPostInfo,
 		pallet_prelude::*,
+		traits::{BalanceStatus, Currency, ExistenceRequirement, ReservableCurrency},
+		transactional,
 	};
 	use frame_system::pallet_prelude::*;
 	use pallet_balances;
-    use sp_runtime::traits::StaticLookup;
+    use sp_runtime::{traits::{CheckedMul, Hash, One, Saturating, UniqueSaturatedInto, Zero}, Permill};
+    use sp_std::vec::Vec;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config + pallet_balances::Config {
+	pub trait Config: frame_system::Config + pallet_balances::Config + pallet_timestamp::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// Currency the cards are paid with.
+        type Currency: ReservableCurrency<Self::AccountId, Balance = Self::Balance>;
+        /// Identifier of an asset cards can be priced in.
+        type AssetId: Parameter + Member + Copy;
+        /// Assets other than `Currency` cards can be priced in.
+        type Assets: AssetTransfer<Self::AccountId, Self::AssetId, Self::Balance>;
+        /// Shortest allowed auction, in blocks.
+        type MinAuctionDuration: Get<Self::BlockNumber>;
+        /// Longest allowed auction, in blocks.
+        type MaxAuctionDuration: Get<Self::BlockNumber>;
+        /// Deposit reserved with every sealed bid, slashed if the bid is not revealed.
+        type SealedBidDeposit: Get<Self::Balance>;
+        /// Most bids a sealed-bid auction accepts.
+        type MaxSealedBids: Get<u32>;
+        /// Most resting orders a single call trades with.
+        type MaxFillsPerCall: Get<u32>;
+        /// Most orders on one side of the order book of a card.
+        type MaxOrdersPerBook: Get<u32>;
+        /// Most kinds of cards each side of a trade offer may give.
+        type MaxTradeItems: Get<u32>;
+        /// Deposit reserved with every trade offer, returned when the offer is closed.
+        type TradeDeposit: Get<Self::Balance>;
+        /// Longest card name, in bytes.
+        type MaxNameLength: Get<u32>;
+        /// Longest card rules text, in bytes.
+        type MaxRulesLength: Get<u32>;
+        /// Most subtypes on the type line of a card, each is at most `MaxNameLength` bytes.
+        type MaxSubtypes: Get<u32>;
+        /// Most colored symbols in the mana cost of a card.
+        type MaxManaPips: Get<u32>;
+        /// Most copies of a common card created at once.
+        type MaxCommonPack: Get<u16>;
+        /// Most copies of an uncommon card created at once.
+        type MaxUncommonPack: Get<u16>;
+        /// Most copies of a rare card created at once.
+        type MaxRarePack: Get<u16>;
+        /// Most copies of a mythic card created at once.
+        type MaxMythicPack: Get<u16>;
+        /// Most copies of a promo card created at once.
+        type MaxPromoPack: Get<u16>;
+        /// Longest set code, in bytes.
+        type MaxSetCodeLength: Get<u32>;
+        /// Most cards in a set.
+        type MaxSetSize: Get<u32>;
+        /// Most previous versions kept in the history of a card, the oldest is dropped first.
+        type MaxErrata: Get<u32>;
+        /// Highest royalty a creator may set, in parts per thousand.
+        type MaxRoyalty: Get<u16>;
+        /// Fee taken from every sale until root changes it, in parts per thousand.
+        type DefaultMarketFee: Get<u16>;
+        /// Account the marketplace fees are paid to.
+        type FeeDestination: Get<Self::AccountId>;
+        /// Most ended auctions, expired orders, expired trade offers and ended rentals of each kind
+        /// processed in one block, the rest is carried over to the next one.
+        type MaxExpiriesPerBlock: Get<u32>;
+        /// Blocks over which `rolling_volume` sums the sales of a card.
+        type VolumeWindow: Get<Self::BlockNumber>;
+        /// Buckets `VolumeWindow` is split into, the rolling volume is summed per bucket.
+        type VolumeBuckets: Get<u32>;
+        /// Most sales kept in the recent trades of a card.
+        type MaxRecentTrades: Get<u32>;
+        /// Longest allowed rental, in blocks.
+        type MaxRentalDuration: Get<Self::BlockNumber>;
 	}
 
 	#[pallet::pallet]
@@ -56,6 +132,33 @@ pub mod pallet {
         Card, OptionQuery
         >;
 
+    /// Creator of a card, who is paid its royalty, and when it was created.
+    #[pallet::storage]
+    #[pallet::getter(fn card_info)]
+    pub type CardInfos<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        CardInfo<T::AccountId, T::BlockNumber, T::Moment>, OptionQuery
+        >;
+
+    /// Copies minted of a card and its max supply, empty for cards created before supplies were tracked.
+    #[pallet::storage]
+    #[pallet::getter(fn supply)]
+    pub type CardSupplies<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        Supply, ValueQuery
+        >;
+
+    /// Share of every resale of a card paid to its creator, in parts per thousand.
+    #[pallet::storage]
+    #[pallet::getter(fn royalty)]
+    pub type CardRoyalties<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        u16, ValueQuery
+        >;
+
     #[pallet::storage]
     #[pallet::getter(fn owners)]
     pub type CardOwners<T: Config> = StorageDoubleMap<
@@ -65,15 +168,278 @@ pub mod pallet {
         u16, OptionQuery
         >;
 
+    /// Copies that are part of `CardOwners` but can not be moved, e.g. listed for sale.
+    #[pallet::storage]
+    #[pallet::getter(fn reserved_cards)]
+    pub type ReservedCards<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat, T::AccountId,
+        Blake2_128Concat, CardId,
+        u16, ValueQuery
+        >;
+
     #[pallet::storage]
-    #[pallet::getter(fn cards_for_sale)]
-    pub type CardsForSale<T: Config> = StorageDoubleMap<
+    #[pallet::getter(fn dutch_sales)]
+    pub type DutchSales<T: Config> = StorageDoubleMap<
         _,
         Blake2_128Concat, CardId,
         Blake2_128Concat, T::AccountId,
-        T::Balance, OptionQuery
+        DutchOffer<T::Balance, T::BlockNumber>, OptionQuery
         >;
-    
+
+    #[pallet::storage]
+    #[pallet::getter(fn asset_listings)]
+    pub type AssetListings<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat, CardId,
+        Blake2_128Concat, T::AccountId,
+        AssetListing<T::AssetId, T::Balance>, OptionQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn auctions)]
+    pub type Auctions<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, AuctionId,
+        Auction<T::AccountId, T::Balance, T::BlockNumber>, OptionQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn next_auction_id)]
+    pub type NextAuctionId<T: Config> = StorageValue<_, AuctionId, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn sealed_auctions)]
+    pub type SealedAuctions<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, AuctionId,
+        SealedAuction<T::AccountId, T::Balance, T::BlockNumber>, OptionQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn sealed_bids)]
+    pub type SealedBids<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat, AuctionId,
+        Blake2_128Concat, T::AccountId,
+        SealedBid<T::Hash, T::Balance>, OptionQuery
+        >;
+
+    /// Auctions to settle at the beginning of a block.
+    #[pallet::storage]
+    #[pallet::getter(fn auctions_ending_at)]
+    pub type AuctionsEndingAt<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::BlockNumber,
+        Vec<AuctionId>, ValueQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn orders)]
+    pub type Orders<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, OrderId,
+        Order<T::AccountId, T::Balance, T::BlockNumber>, OptionQuery
+        >;
+
+    /// Sell orders of a card, cheapest first and oldest first within a price.
+    #[pallet::storage]
+    #[pallet::getter(fn asks)]
+    pub type Asks<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        Vec<(T::Balance, OrderId)>, ValueQuery
+        >;
+
+    /// Buy orders of a card, highest price first and oldest first within a price.
+    #[pallet::storage]
+    #[pallet::getter(fn bids)]
+    pub type Bids<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        Vec<(T::Balance, OrderId)>, ValueQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn next_order_id)]
+    pub type NextOrderId<T: Config> = StorageValue<_, OrderId, ValueQuery>;
+
+    /// Orders to cancel at the beginning of a block.
+    #[pallet::storage]
+    #[pallet::getter(fn orders_expiring_at)]
+    pub type OrdersExpiringAt<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::BlockNumber,
+        Vec<OrderId>, ValueQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn trades)]
+    pub type Trades<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, TradeId,
+        TradeOffer<T::AccountId, T::Balance, T::BlockNumber>, OptionQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn next_trade_id)]
+    pub type NextTradeId<T: Config> = StorageValue<_, TradeId, ValueQuery>;
+
+    /// Trade offers to cancel at the beginning of a block.
+    #[pallet::storage]
+    #[pallet::getter(fn trades_expiring_at)]
+    pub type TradesExpiringAt<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::BlockNumber,
+        Vec<TradeId>, ValueQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn rental_offers)]
+    pub type RentalOffers<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat, CardId,
+        Blake2_128Concat, T::AccountId,
+        RentalOffer<T::Balance, T::BlockNumber>, OptionQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn rentals)]
+    pub type Rentals<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, RentalId,
+        Rental<T::AccountId, T::BlockNumber>, OptionQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn next_rental_id)]
+    pub type NextRentalId<T: Config> = StorageValue<_, RentalId, ValueQuery>;
+
+    /// Rentals to end at the beginning of a block.
+    #[pallet::storage]
+    #[pallet::getter(fn rentals_ending_at)]
+    pub type RentalsEndingAt<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::BlockNumber,
+        Vec<RentalId>, ValueQuery
+        >;
+
+    /// Copies a renter may use but not move.
+    #[pallet::storage]
+    #[pallet::getter(fn usage_rights)]
+    pub type UsageRights<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat, T::AccountId,
+        Blake2_128Concat, CardId,
+        u16, ValueQuery
+        >;
+
+    /// Copies of an owner that are rented out, these are also reserved.
+    #[pallet::storage]
+    #[pallet::getter(fn lent_cards)]
+    pub type LentCards<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat, T::AccountId,
+        Blake2_128Concat, CardId,
+        u16, ValueQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn market_stats)]
+    pub type CardStats<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        MarketStats<T::Balance, T::BlockNumber>, OptionQuery
+        >;
+
+    /// Latest sales of a card, oldest first.
+    #[pallet::storage]
+    #[pallet::getter(fn recent_trades)]
+    pub type RecentTrades<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        Vec<SaleRecord<T::AccountId, T::Balance, T::BlockNumber>>, ValueQuery
+        >;
+
+    /// Balance paid for a card per bucket of `VolumeWindow / VolumeBuckets` blocks, as the first
+    /// block of the bucket and the volume. Buckets are kept in a ring of `VolumeBuckets` slots,
+    /// a new bucket overwrites the one a window before it.
+    #[pallet::storage]
+    #[pallet::getter(fn volume_history)]
+    pub type VolumeHistory<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat, CardId,
+        Twox64Concat, u32,
+        (T::BlockNumber, T::Balance), ValueQuery
+        >;
+
+    #[pallet::type_value]
+    pub fn DefaultMarketFee<T: Config>() -> u16 { T::DefaultMarketFee::get() }
+
+    /// Share of every sale paid to `FeeDestination`, in parts per thousand.
+    #[pallet::storage]
+    #[pallet::getter(fn market_fee)]
+    pub type MarketFee<T: Config> = StorageValue<_, u16, ValueQuery, DefaultMarketFee<T>>;
+
+    /// Set as stored in `Sets`.
+    pub type CardSetOf<T> = CardSet<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn sets)]
+    pub type Sets<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, SetId,
+        CardSetOf<T>, OptionQuery
+        >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn next_set_id)]
+    pub type NextSetId<T: Config> = StorageValue<_, SetId, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn set_by_code)]
+    pub type SetCodes<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, Vec<u8>,
+        SetId, OptionQuery
+        >;
+
+    /// Cards of a set ordered by collector number.
+    #[pallet::storage]
+    #[pallet::getter(fn set_cards)]
+    pub type SetCards<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, SetId,
+        Vec<(u32, CardId)>, ValueQuery
+        >;
+
+    /// Set and collector number of a card created into a set.
+    #[pallet::storage]
+    #[pallet::getter(fn printing)]
+    pub type CardPrintings<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        (SetId, u32), OptionQuery
+        >;
+
+    /// Previous versions of the rules and stats of a card, oldest first.
+    #[pallet::storage]
+    #[pallet::getter(fn card_history)]
+    pub type CardHistory<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        Vec<Erratum<T::BlockNumber>>, ValueQuery
+        >;
+
+    /// Cards that can not get errata anymore.
+    #[pallet::storage]
+    #[pallet::getter(fn is_frozen)]
+    pub type FrozenCards<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat, CardId,
+        bool, ValueQuery
+        >;
+
     #[pallet::type_value]
     pub fn DefaultPreviousId() -> CardId { 0 }
 
@@ -81,6 +447,10 @@ pub mod pallet {
     #[pallet::getter(fn previous_card_id)]
     pub type PreviousCardId<T: Config> = StorageValue<_, CardId, ValueQuery, DefaultPreviousId>;
 
+    /// Layout of the pallet storage, see `migration`.
+    #[pallet::storage]
+    pub(crate) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
     #[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
         pub creator: T::AccountId,
@@ -114,6 +484,7 @@ pub mod pallet {
 	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
 		fn build(&self) {
             <CreatorRegistry<T>>::insert(&self.creator, ());
+            <StorageVersion<T>>::put(Releases::LATEST);
 		}
 	}
 
@@ -122,18 +493,88 @@ pub mod pallet {
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-        /// \[creator, card_id, amount\]
-		CardCreated(T::AccountId, CardId, u16),
-        /// \[old owner, card_id, new owner\]
-        CardTransferred(T::AccountId, CardId, T::AccountId),
+        /// \[creator, card_id, amount, rarity\]
+		CardCreated(T::AccountId, CardId, u16, Rarity),
+        /// \[old owner, card_id, new owner, amount\]
+        CardTransferred(T::AccountId, CardId, T::AccountId, u16),
         /// \[assigner, new creator\]
         CreatorAssigned(T::AccountId, T::AccountId),
         /// \[assigner, not a creator anymore\]
         CreatorWithdrawn(T::AccountId, T::AccountId),
-        /// \[card_id, owner, price\]
-        CardSetForSale(CardId, T::AccountId, T::Balance),
+        /// \[card_id, owner, price, amount\]
+        CardSetForSale(CardId, T::AccountId, T::Balance, u16),
         /// \[card_id, owner\]
         CardRemovedFromSale(CardId, T::AccountId),
+        /// \[auction_id, seller, card_id, amount, reserve price, end\]
+        AuctionStarted(AuctionId, T::AccountId, CardId, u16, T::Balance, T::BlockNumber),
+        /// \[auction_id, bidder, bid\]
+        BidPlaced(AuctionId, T::AccountId, T::Balance),
+        /// \[auction_id, winner and price if there were bids\]
+        AuctionSettled(AuctionId, Option<(T::AccountId, T::Balance)>),
+        /// \[auction_id\]
+        AuctionCancelled(AuctionId),
+        /// \[card_id, owner, start price, end price, start, end, amount\]
+        CardSetForDutchSale(CardId, T::AccountId, T::Balance, T::Balance, T::BlockNumber, T::BlockNumber, u16),
+        /// \[card_id, owner\]
+        CardRemovedFromDutchSale(CardId, T::AccountId),
+        /// \[auction_id, seller, card_id, amount, reserve price, commit end, reveal end\]
+        SealedAuctionStarted(AuctionId, T::AccountId, CardId, u16, T::Balance, T::BlockNumber, T::BlockNumber),
+        /// \[auction_id, bidder\]
+        BidCommitted(AuctionId, T::AccountId),
+        /// \[auction_id, bidder, bid\]
+        BidRevealed(AuctionId, T::AccountId, T::Balance),
+        /// \[auction_id, bidder, slashed deposit\]
+        BidDepositSlashed(AuctionId, T::AccountId, T::Balance),
+        /// \[order_id, owner, card_id, side, price, amount\]
+        OrderPlaced(OrderId, T::AccountId, CardId, Side, T::Balance, u16),
+        /// \[order_id, counterparty, amount, price\]
+        OrderFilled(OrderId, T::AccountId, u16, T::Balance),
+        /// \[order_id\]
+        OrderCancelled(OrderId),
+        /// \[order_id\]
+        OrderExpired(OrderId),
+        /// \[order_id\]
+        OrderEvicted(OrderId),
+        /// \[trade_id, proposer, counterparty\]
+        TradeProposed(TradeId, T::AccountId, T::AccountId),
+        /// \[trade_id\]
+        TradeAccepted(TradeId),
+        /// \[trade_id\]
+        TradeCancelled(TradeId),
+        /// \[trade_id\]
+        TradeExpired(TradeId),
+        /// \[card_id, creator, royalty\]
+        RoyaltyPaid(CardId, T::AccountId, T::Balance),
+        /// \[new fee\]
+        MarketFeeChanged(u16),
+        /// \[card_id, seller, buyer, amount, price\]
+        CardSold(CardId, T::AccountId, T::AccountId, u16, T::Balance),
+        /// \[card_id, owner, asset_id, price, amount\]
+        CardSetForAssetSale(CardId, T::AccountId, T::AssetId, T::Balance, u16),
+        /// \[card_id, owner\]
+        CardRemovedFromAssetSale(CardId, T::AccountId),
+        /// \[card_id, seller, buyer, amount, asset_id, price\]
+        CardSoldForAsset(CardId, T::AccountId, T::AccountId, u16, T::AssetId, T::Balance),
+        /// \[card_id, owner, price, duration, amount\]
+        CardOfferedForRent(CardId, T::AccountId, T::Balance, T::BlockNumber, u16),
+        /// \[card_id, owner\]
+        RentalOfferRemoved(CardId, T::AccountId),
+        /// \[rental_id, card_id, owner, renter, amount, end\]
+        CardRented(RentalId, CardId, T::AccountId, T::AccountId, u16, T::BlockNumber),
+        /// \[rental_id\]
+        RentalEnded(RentalId),
+        /// \[set_id, creator, code\]
+        SetCreated(SetId, T::AccountId, Vec<u8>),
+        /// \[set_id, card_id, collector number\]
+        CardAddedToSet(SetId, CardId, u32),
+        /// \[set_id\]
+        SetClosed(SetId),
+        /// \[card_id, previous versions kept in `CardHistory`\]
+        CardErrata(CardId, u32),
+        /// \[card_id\]
+        CardFrozen(CardId),
+        /// \[card_id, receiver, amount\]
+        CardsMinted(CardId, T::AccountId, u16),
 	}
 
 	// Errors inform users that something went wrong.
@@ -153,10 +594,209 @@ pub mod pallet {
         CardNotOwned,
         /// Not for sale
         CardNotForSale,
+        /// Buyer can not pay the price
+        InsufficientBalance,
+        /// Buyer and seller are the same account
+        CannotBuyOwnCard,
+        /// Amount of copies must be greater than zero
+        ZeroAmount,
+        /// Account owns fewer copies than requested
+        NotEnoughCards,
+        /// Requested copies are reserved, e.g. listed for sale
+        CardsReserved,
+        /// Seller offers fewer copies than requested
+        NotEnoughCardsForSale,
+        /// Price calculation overflowed
+        PriceOverflow,
+        /// Price is higher than the buyer agreed to pay
+        PriceAboveMaximum,
+        /// Auction duration is out of the allowed range
+        InvalidAuctionDuration,
+        /// No auction with such id
+        AuctionNotFound,
+        /// Auction does not accept bids anymore
+        AuctionEnded,
+        /// Bid is below the reserve price or the minimum increment
+        BidTooLow,
+        /// Auction with bids can not be cancelled
+        AuctionHasBids,
+        /// Can not store an auction
+        AuctionIdOverflow,
+        /// Price must not rise and the sale must end after it starts
+        InvalidDutchSale,
+        /// Sale has not started yet
+        SaleNotStarted,
+        /// Account has already committed a bid
+        BidAlreadyCommitted,
+        /// Auction has as many bids as it accepts
+        TooManyBids,
+        /// No committed bid to reveal
+        BidNotCommitted,
+        /// Bid has already been revealed
+        BidAlreadyRevealed,
+        /// Auction is not in its reveal phase
+        NotRevealPhase,
+        /// Revealed bid does not match the commitment
+        InvalidReveal,
+        /// No card with such id
+        UnknownCard,
+        /// Expiry block is not in the future
+        InvalidExpiry,
+        /// No order with such id
+        OrderNotFound,
+        /// Order wants fewer copies than offered
+        NotEnoughCardsWanted,
+        /// Can not store an order
+        OrderIdOverflow,
+        /// Order is on the other side of the book
+        WrongOrderSide,
+        /// Order book of the card is full and the order does not beat its worst order
+        OrderBookFull,
+        /// Price must be greater than zero
+        ZeroPrice,
+        /// Proposer and counterparty are the same account
+        CannotTradeWithSelf,
+        /// Trade offer moves nothing
+        EmptyTrade,
+        /// Trade offer lists more kinds of cards than allowed
+        TooManyTradeItems,
+        /// Trade offer lists a card twice on the same side
+        DuplicateTradeItem,
+        /// No trade offer with such id
+        TradeNotFound,
+        /// Can not store a trade offer
+        TradeIdOverflow,
+        /// Royalty is above `MaxRoyalty`
+        RoyaltyTooHigh,
+        /// Card name is longer than `MaxNameLength`
+        NameTooLong,
+        /// Card rules are longer than `MaxRulesLength`
+        RulesTooLong,
+        /// Card text is not UTF-8 or contains control characters
+        InvalidEncoding,
+        /// Card color has undefined bits
+        InvalidColor,
+        /// Type line has no types, repeats an entry or has an empty or too long subtype
+        InvalidTypeLine,
+        /// Type line has more than `MaxSubtypes` subtypes
+        TooManySubtypes,
+        /// Mana cost has more than `MaxManaPips` colored symbols
+        TooManyManaPips,
+        /// Mana symbol is not a single color or a hybrid of two different colors
+        InvalidManaPip,
+        /// Card color differs from the colors of its mana cost
+        ColorMismatch,
+        /// Power and toughness on a card that is not a creature or loyalty on one that is not a
+        /// planeswalker, or a creature without power and toughness
+        InvalidStats,
+        /// More copies than the rarity of the card allows in one pack
+        PackTooLarge,
+        /// No set with such id
+        SetNotFound,
+        /// Set does not accept new cards
+        SetClosed,
+        /// Set code is empty, longer than `MaxSetCodeLength` or not ASCII letters and digits
+        InvalidSetCode,
+        /// Another set has this code
+        SetCodeTaken,
+        /// Set size is zero or above `MaxSetSize`
+        InvalidSetSize,
+        /// Collector number is zero or above the set size
+        InvalidCollectorNumber,
+        /// Another card of the set has this collector number
+        CollectorNumberTaken,
+        /// Can not store a set
+        SetIdOverflow,
+        /// Card does not accept errata anymore
+        CardIsFrozen,
+        /// Minting would exceed the max supply of the card
+        SupplyExceeded,
+        /// Account would own more copies than can be counted
+        TooManyCopies,
+        /// Rental duration is zero or above `MaxRentalDuration`
+        InvalidRentalDuration,
+        /// Can not store a rental
+        RentalIdOverflow,
+        /// Fee is above one thousand parts per thousand
+        InvalidFee,
+        /// Trade offer has expired
+        TradeExpired,
+        /// Order has expired
+        OrderExpired,
 	}
 
     #[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_runtime_upgrade() -> Weight {
+            migration::migrate::<T>()
+        }
+
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let mut ending = <AuctionsEndingAt<T>>::take(now);
+            let limit = T::MaxExpiriesPerBlock::get() as usize;
+            let mut weight = T::DbWeight::get().reads_writes(2, 2);
+            if ending.len() > limit {
+                let carried = ending.split_off(limit);
+                <AuctionsEndingAt<T>>::mutate(now + One::one(), |ids| ids.extend(carried));
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+            }
+            for auction_id in ending {
+                if let Some(auction) = <Auctions<T>>::take(auction_id) {
+                    Self::settle_auction(auction_id, auction);
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(8, 9));
+                } else if let Some(auction) = <SealedAuctions<T>>::take(auction_id) {
+                    let bids = auction.bids as Weight;
+                    Self::settle_sealed_auction(auction_id, auction);
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(8 + bids, 9 + bids));
+                }
+            }
+
+            let mut expiring = <OrdersExpiringAt<T>>::take(now);
+            if expiring.len() > limit {
+                let carried = expiring.split_off(limit);
+                <OrdersExpiringAt<T>>::mutate(now + One::one(), |ids| ids.extend(carried));
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+            }
+            for order_id in expiring {
+                weight = weight.saturating_add(T::DbWeight::get().reads(1));
+                if let Some(order) = Self::orders(order_id) {
+                    Self::remove_order(order_id, &order);
+                    Self::deposit_event(Event::OrderExpired(order_id));
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(2, 3));
+                }
+            }
+
+            let mut expiring = <TradesExpiringAt<T>>::take(now);
+            if expiring.len() > limit {
+                let carried = expiring.split_off(limit);
+                <TradesExpiringAt<T>>::mutate(now + One::one(), |ids| ids.extend(carried));
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+            }
+            for trade_id in expiring {
+                if let Some(trade) = <Trades<T>>::take(trade_id) {
+                    Self::release_trade(&trade);
+                    Self::deposit_event(Event::TradeExpired(trade_id));
+                    weight = weight.saturating_add(T::DbWeight::get()
+                        .reads_writes(2 + trade.give.len() as Weight, 2 + trade.give.len() as Weight));
+                }
+            }
+
+            let mut ending = <RentalsEndingAt<T>>::take(now);
+            if ending.len() > limit {
+                let carried = ending.split_off(limit);
+                <RentalsEndingAt<T>>::mutate(now + One::one(), |ids| ids.extend(carried));
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+            }
+            for rental_id in ending {
+                if let Some(rental) = <Rentals<T>>::take(rental_id) {
+                    Self::end_rental(&rental);
+                    Self::deposit_event(Event::RentalEnded(rental_id));
+                    weight = weight.saturating_add(T::DbWeight::get().reads_writes(4, 4));
+                }
+            }
+            weight
+        }
+    }
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
@@ -183,91 +823,75 @@ pub mod pallet {
             Ok(().into())
         }
 
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn create_card_pack(origin: OriginFor<T>, card: Card, amount: u16) -> DispatchResultWithPostInfo {
+        /// Creates `amount` copies of a new card. `royalty` is the share of every later resale
+        /// paid to the creator, in parts per thousand. `max_supply` caps the copies ever minted.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 6))]
+		pub fn create_card_pack(origin: OriginFor<T>, card: Card, amount: u16, royalty: u16, max_supply: Option<u32>)
+            -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
             ensure!(<CreatorRegistry<T>>::contains_key(&who), Error::<T>::NoPermission);
-            
-            let i = Self::previous_card_id();
-            let nextid = i.checked_add(1);
-            match nextid {
-                Some(id) => {
-                    // Update storage.
-                    <CardRegistry<T>>::insert(id, card);
-                    <PreviousCardId<T>>::put(id);
-                    <CardOwners<T>>::insert(&who, id, amount);
-                    // Emit an event.
-                    Self::deposit_event(Event::CardCreated(who, id, amount));
-                    // Return a successful DispatchResultWithPostInfo
-                    Ok(().into())
-                },
-                None => Err(Error::<T>::IdStorageOverflow)?
-            }	
+
+            Self::do_create_card(who, card, amount, royalty, max_supply)?;
+            Ok(().into())
 		}
 
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn set_card_for_sale_with_price(origin: OriginFor<T>, card_id: CardId, price: T::Balance) 
-            ->  DispatchResultWithPostInfo {
+        /// Offers `amount` copies of `card_id` for `price` each.
+        ///
+        /// The offer first fills the best buy orders paying at least `price`, the rest stays in
+        /// the order book with its copies reserved until it is filled, cancelled or expires in
+        /// block `expires`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+            4 + 9 * T::MaxFillsPerCall::get() as Weight, 4 + 9 * T::MaxFillsPerCall::get() as Weight))]
+        #[transactional]
+		pub fn set_card_for_sale_with_price(origin: OriginFor<T>, card_id: CardId, price: T::Balance, amount: u16,
+            expires: Option<T::BlockNumber>) ->  DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
-            ensure!(<CardOwners<T>>::contains_key(&who, card_id), Error::<T>::CardNotOwned);
 
-            <CardsForSale<T>>::insert(card_id, &who, price);
-
-            Self::deposit_event(Event::CardSetForSale(card_id, who, price));
+            Self::place_order(Order { owner: who, card_id, side: Side::Ask, price, amount, expires })?;
             Ok(().into())
         }
 
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn remove_card_from_sale(origin: OriginFor<T>, card_id: CardId) 
+        /// Cancels every sell order of the caller for `card_id`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+            1 + 4 * T::MaxOrdersPerBook::get() as Weight, 4 * T::MaxOrdersPerBook::get() as Weight))]
+		pub fn remove_card_from_sale(origin: OriginFor<T>, card_id: CardId)
             ->  DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
-            ensure!(<CardsForSale<T>>::contains_key(card_id, &who), Error::<T>::CardNotForSale);
+            let orders: Vec<_> = Self::asks(card_id).into_iter()
+                .filter_map(|(_, order_id)| Self::orders(order_id).map(|order| (order_id, order)))
+                .filter(|(_, order)| order.owner == who)
+                .collect();
+            ensure!(!orders.is_empty(), Error::<T>::CardNotForSale);
 
-            <CardsForSale<T>>::remove(card_id, &who);
-
-            Self::deposit_event(Event::CardRemovedFromSale(card_id, who));
+            for (order_id, order) in orders {
+                Self::remove_order(order_id, &order);
+                Self::deposit_event(Event::OrderCancelled(order_id));
+            }
             Ok(().into())
         }
 
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn buy(origin: OriginFor<T>, card_id: CardId, card_owner: T::AccountId)   
+        /// Buys `amount` copies from the sell order `order_id`.
+        ///
+        /// Fails if the price per copy is above `max_price`, so the buyer never pays more
+        /// than `max_price * amount` in total.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(9, 10))]
+        #[transactional]
+		pub fn buy(origin: OriginFor<T>, order_id: OrderId, amount: u16, max_price: T::Balance)   
             ->  DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
 
-            <CardsForSale<T>>::try_mutate_exists(card_id, &card_owner, |price| {
-                ensure!(<CardOwners<T>>::contains_key(&card_owner, card_id), Error::<T>::CardNotOwned);
-                ensure!(<CardsForSale<T>>::contains_key(card_id, &card_owner), Error::<T>::CardNotForSale);
-
-                let price = price.take().ok_or( Error::<T>::CardNotForSale)?;
-                <CardOwners<T>>::try_mutate_exists(card_owner.clone(), card_id, |amount| {
-                    pallet_balances::Call::<T>::transfer_keep_alive(
-                        <T::Lookup as StaticLookup>::unlookup(card_owner.clone()), price);
-                    match amount { 
-                        Some(owner_amount) => {
-                            if owner_amount == &1u16 {
-                                amount.take();
-                            } else {
-                                let new_amount = owner_amount.checked_sub(1);
-                                if let Some(a) = new_amount {
-                                    *amount = Some(a);
-                                } else {
-                                    Err(Error::<T>::CardNotOwned)?
-                                }
-                            }
-
-                            match <CardOwners<T>>::try_get(&who, card_id) {
-                                Ok(amount) => <CardOwners<T>>::insert(&who, card_id, amount+1),
-                                _ => <CardOwners<T>>::insert(&who, card_id, 1),
-                            }
-                            Self::deposit_event(Event::CardTransferred(card_owner.clone(), card_id, who));
-                            Ok(().into())
-                        },
-                        None => {
-                            Err(Error::<T>::CardNotOwned)?
-                        }
-                    }
-                })
-            })
+            let mut order = Self::orders(order_id).ok_or(Error::<T>::OrderNotFound)?;
+            ensure!(order.side == Side::Ask, Error::<T>::WrongOrderSide);
+            ensure!(who != order.owner, Error::<T>::CannotBuyOwnCard);
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+            ensure!(order.amount >= amount, Error::<T>::NotEnoughCardsForSale);
+            ensure!(order.price <= max_price, Error::<T>::PriceAboveMaximum);
+            ensure!(!order.is_expired(&<frame_system::Module<T>>::block_number()), Error::<T>::OrderExpired);
+
+            if Self::fill_order(order_id, &mut order, &who, amount, None)? {
+                Self::remove_from_book(order.card_id, order.side, order_id);
+            }
+            Ok(().into())
         }
 
         #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
@@ -275,40 +899,1280 @@ pub mod pallet {
              -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
 
-            <CardOwners<T>>::try_mutate_exists(who.clone(), card_id, |amount| {
-                match amount {
-                    Some(owner_amount) => {
-                        if owner_amount == &1u16 {
-                            amount.take();
-                            <CardsForSale<T>>::remove(card_id, &who);
-                        } else {
-                            let new_amount = owner_amount.checked_sub(1);
-                            if let Some(a) = new_amount {
-                                *amount = Some(a);
-                            } else {
-                                Err(Error::<T>::CardNotOwned)?
-                            }
-                        }
+            Self::do_transfer(&who, &account, card_id, 1)?;
+
+            Self::deposit_event(Event::CardTransferred(who, card_id, account, 1));
+            Ok(().into())
+        }
+
+        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn show_user_cards(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+            todo!();
+            Ok(().into())
+        }
+
+        /// Starts an auction for `amount` copies of `card_id` lasting `duration` blocks.
+        ///
+        /// The copies are reserved until the auction is settled or cancelled.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4))]
+        #[transactional]
+        pub fn start_auction(origin: OriginFor<T>, card_id: CardId, amount: u16, reserve_price: T::Balance,
+            min_increment: T::Balance, duration: T::BlockNumber) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(duration >= T::MinAuctionDuration::get() && duration <= T::MaxAuctionDuration::get(),
+                Error::<T>::InvalidAuctionDuration);
+
+            let auction_id = Self::next_auction_id();
+            let next_id = auction_id.checked_add(1).ok_or(Error::<T>::AuctionIdOverflow)?;
+            Self::reserve_cards(&who, card_id, amount)?;
+
+            let end = <frame_system::Module<T>>::block_number().saturating_add(duration);
+            <Auctions<T>>::insert(auction_id, Auction {
+                seller: who.clone(),
+                card_id,
+                amount,
+                reserve_price,
+                min_increment,
+                end,
+                best_bid: None,
+            });
+            <AuctionsEndingAt<T>>::append(end, auction_id);
+            <NextAuctionId<T>>::put(next_id);
+
+            Self::deposit_event(Event::AuctionStarted(auction_id, who, card_id, amount, reserve_price, end));
+            Ok(().into())
+        }
+
+        /// Bids `value` for the whole lot. The bid is reserved, the outbid bidder is refunded.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        #[transactional]
+        pub fn bid(origin: OriginFor<T>, auction_id: AuctionId, value: T::Balance) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            <Auctions<T>>::try_mutate(auction_id, |auction| -> DispatchResult {
+                let auction = auction.as_mut().ok_or(Error::<T>::AuctionNotFound)?;
+                ensure!(<frame_system::Module<T>>::block_number() < auction.end, Error::<T>::AuctionEnded);
+                ensure!(who != auction.seller, Error::<T>::CannotBuyOwnCard);
+
+                let min_bid = match &auction.best_bid {
+                    Some((_, best)) => best.saturating_add(auction.min_increment),
+                    None => auction.reserve_price,
+                };
+                ensure!(value >= min_bid && !value.is_zero(), Error::<T>::BidTooLow);
+
+                if let Some((bidder, best)) = auction.best_bid.take() {
+                    T::Currency::unreserve(&bidder, best);
+                }
+                T::Currency::reserve(&who, value).map_err(|_| Error::<T>::InsufficientBalance)?;
+                auction.best_bid = Some((who.clone(), value));
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::BidPlaced(auction_id, who, value));
+            Ok(().into())
+        }
+
+        /// Cancels an auction nobody has bid on yet.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        pub fn cancel_auction(origin: OriginFor<T>, auction_id: AuctionId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let auction = Self::auctions(auction_id).ok_or(Error::<T>::AuctionNotFound)?;
+            ensure!(auction.seller == who, Error::<T>::NoPermission);
+            ensure!(auction.best_bid.is_none(), Error::<T>::AuctionHasBids);
+
+            <Auctions<T>>::remove(auction_id);
+            <AuctionsEndingAt<T>>::mutate(auction.end, |ids| ids.retain(|id| *id != auction_id));
+            Self::unreserve_cards(&who, auction.card_id, auction.amount);
+
+            Self::deposit_event(Event::AuctionCancelled(auction_id));
+            Ok(().into())
+        }
+
+        /// Offers `amount` copies of `card_id` at a price falling linearly from `start_price`
+        /// at block `start` to `end_price` at block `end`, replacing a previous such offer.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+        #[transactional]
+        pub fn set_card_for_dutch_sale(origin: OriginFor<T>, card_id: CardId, start_price: T::Balance,
+            end_price: T::Balance, start: T::BlockNumber, end: T::BlockNumber, amount: u16)
+            -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(<CardOwners<T>>::contains_key(&who, card_id), Error::<T>::CardNotOwned);
+            ensure!(start_price >= end_price && start < end, Error::<T>::InvalidDutchSale);
+
+            if let Some(offer) = <DutchSales<T>>::take(card_id, &who) {
+                Self::unreserve_cards(&who, card_id, offer.amount);
+            }
+            Self::reserve_cards(&who, card_id, amount)?;
+            <DutchSales<T>>::insert(card_id, &who, DutchOffer { start_price, end_price, start, end, amount });
+
+            Self::deposit_event(Event::CardSetForDutchSale(card_id, who, start_price, end_price, start, end, amount));
+            Ok(().into())
+        }
+
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+        pub fn remove_card_from_dutch_sale(origin: OriginFor<T>, card_id: CardId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let offer = <DutchSales<T>>::take(card_id, &who).ok_or(Error::<T>::CardNotForSale)?;
+
+            Self::unreserve_cards(&who, card_id, offer.amount);
+
+            Self::deposit_event(Event::CardRemovedFromDutchSale(card_id, who));
+            Ok(().into())
+        }
+
+        /// Buys `amount` copies from a descending price offer at the price of the current block.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(8, 9))]
+        #[transactional]
+        pub fn buy_dutch(origin: OriginFor<T>, card_id: CardId, card_owner: T::AccountId, amount: u16,
+            max_price: T::Balance) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let mut offer = Self::dutch_sales(card_id, &card_owner).ok_or(Error::<T>::CardNotForSale)?;
+            ensure!(who != card_owner, Error::<T>::CannotBuyOwnCard);
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+            ensure!(offer.amount >= amount, Error::<T>::NotEnoughCardsForSale);
+
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now >= offer.start, Error::<T>::SaleNotStarted);
+            let price = offer.price_at(now);
+            ensure!(price <= max_price, Error::<T>::PriceAboveMaximum);
+
+            let total = price.checked_mul(&T::Balance::from(amount)).ok_or(Error::<T>::PriceOverflow)?;
+            Self::settle_trade(&card_owner, &who, card_id, amount, total, true, false)?;
+
+            offer.amount -= amount;
+            if offer.amount == 0 {
+                <DutchSales<T>>::remove(card_id, &card_owner);
+            } else {
+                <DutchSales<T>>::insert(card_id, &card_owner, offer);
+            }
+            Ok(().into())
+        }
+
+        /// Starts a sealed-bid auction for `amount` copies of `card_id`.
+        ///
+        /// Bids are committed for `commit_duration` blocks and revealed during the following
+        /// `reveal_duration` blocks. The best revealed bid wins and pays the second best bid,
+        /// but no less than `reserve_price`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 4))]
+        #[transactional]
+        pub fn start_sealed_auction(origin: OriginFor<T>, card_id: CardId, amount: u16, reserve_price: T::Balance,
+            commit_duration: T::BlockNumber, reveal_duration: T::BlockNumber) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            for duration in &[commit_duration, reveal_duration] {
+                ensure!(*duration >= T::MinAuctionDuration::get() && *duration <= T::MaxAuctionDuration::get(),
+                    Error::<T>::InvalidAuctionDuration);
+            }
+
+            let auction_id = Self::next_auction_id();
+            let next_id = auction_id.checked_add(1).ok_or(Error::<T>::AuctionIdOverflow)?;
+            Self::reserve_cards(&who, card_id, amount)?;
+
+            let commit_end = <frame_system::Module<T>>::block_number().saturating_add(commit_duration);
+            let reveal_end = commit_end.saturating_add(reveal_duration);
+            <SealedAuctions<T>>::insert(auction_id, SealedAuction {
+                seller: who.clone(),
+                card_id,
+                amount,
+                reserve_price,
+                commit_end,
+                reveal_end,
+                bids: 0,
+                best_bid: None,
+                second_bid: None,
+            });
+            <AuctionsEndingAt<T>>::append(reveal_end, auction_id);
+            <NextAuctionId<T>>::put(next_id);
+
+            Self::deposit_event(Event::SealedAuctionStarted(auction_id, who, card_id, amount, reserve_price,
+                commit_end, reveal_end));
+            Ok(().into())
+        }
+
+        /// Commits a hidden bid, `commitment` is the hash of `(bidder, value, salt)`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        #[transactional]
+        pub fn commit_bid(origin: OriginFor<T>, auction_id: AuctionId, commitment: T::Hash) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            <SealedAuctions<T>>::try_mutate(auction_id, |auction| -> DispatchResult {
+                let auction = auction.as_mut().ok_or(Error::<T>::AuctionNotFound)?;
+                ensure!(<frame_system::Module<T>>::block_number() < auction.commit_end, Error::<T>::AuctionEnded);
+                ensure!(who != auction.seller, Error::<T>::CannotBuyOwnCard);
+                ensure!(!<SealedBids<T>>::contains_key(auction_id, &who), Error::<T>::BidAlreadyCommitted);
+                ensure!(auction.bids < T::MaxSealedBids::get(), Error::<T>::TooManyBids);
+
+                let deposit = T::SealedBidDeposit::get();
+                T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::InsufficientBalance)?;
+                <SealedBids<T>>::insert(auction_id, &who, SealedBid { commitment, deposit, revealed: None });
+                auction.bids += 1;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::BidCommitted(auction_id, who));
+            Ok(().into())
+        }
 
-                        match <CardOwners<T>>::try_get(&account, card_id) {
-                            Ok(amount) => <CardOwners<T>>::insert(&account, card_id, amount+1),
-                            _ => <CardOwners<T>>::insert(&account, card_id, 1),
+        /// Reveals a committed bid. Only the best bid stays reserved.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        #[transactional]
+        pub fn reveal_bid(origin: OriginFor<T>, auction_id: AuctionId, value: T::Balance, salt: Vec<u8>)
+            -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            <SealedAuctions<T>>::try_mutate(auction_id, |auction| -> DispatchResult {
+                let auction = auction.as_mut().ok_or(Error::<T>::AuctionNotFound)?;
+                let now = <frame_system::Module<T>>::block_number();
+                ensure!(now >= auction.commit_end && now < auction.reveal_end, Error::<T>::NotRevealPhase);
+
+                let mut bid = Self::sealed_bids(auction_id, &who).ok_or(Error::<T>::BidNotCommitted)?;
+                ensure!(bid.revealed.is_none(), Error::<T>::BidAlreadyRevealed);
+                ensure!(T::Hashing::hash_of(&(&who, value, salt)) == bid.commitment, Error::<T>::InvalidReveal);
+                ensure!(T::Currency::can_reserve(&who, value), Error::<T>::InsufficientBalance);
+
+                bid.revealed = Some(value);
+                <SealedBids<T>>::insert(auction_id, &who, bid);
+
+                if value < auction.reserve_price {
+                    return Ok(());
+                }
+                match auction.best_bid.take() {
+                    Some((best_bidder, best)) if best >= value => {
+                        auction.second_bid = auction.second_bid.max(Some(value));
+                        auction.best_bid = Some((best_bidder, best));
+                    },
+                    previous => {
+                        if let Some((best_bidder, best)) = previous {
+                            T::Currency::unreserve(&best_bidder, best);
+                            auction.second_bid = Some(best);
                         }
-                        Self::deposit_event(Event::CardTransferred(who, card_id, account));
-                        Ok(().into())
+                        T::Currency::reserve(&who, value)?;
+                        auction.best_bid = Some((who.clone(), value));
                     },
-                    None => {
-                        Err(Error::<T>::CardNotOwned)?
-                    }
                 }
-            })
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::BidRevealed(auction_id, who, value));
+            Ok(().into())
+        }
+
+        /// Offers to buy up to `amount` copies of `card_id` for `price` each.
+        ///
+        /// The order first fills the cheapest sell orders asking at most `price`, the rest stays
+        /// in the order book with its price reserved until it is filled, cancelled or expires
+        /// in block `expires`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+            4 + 9 * T::MaxFillsPerCall::get() as Weight, 4 + 9 * T::MaxFillsPerCall::get() as Weight))]
+        #[transactional]
+        pub fn place_buy_order(origin: OriginFor<T>, card_id: CardId, price: T::Balance, amount: u16,
+            expires: Option<T::BlockNumber>) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            Self::place_order(Order { owner: who, card_id, side: Side::Bid, price, amount, expires })?;
+            Ok(().into())
+        }
+
+        /// Cancels an order and releases the copies or funds it reserves.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        pub fn cancel_order(origin: OriginFor<T>, order_id: OrderId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let order = Self::orders(order_id).ok_or(Error::<T>::OrderNotFound)?;
+            ensure!(order.owner == who, Error::<T>::NoPermission);
+
+            Self::remove_order(order_id, &order);
+
+            Self::deposit_event(Event::OrderCancelled(order_id));
+            Ok(().into())
+        }
+
+        /// Sells `amount` free copies to the buy order `order_id` at the order's price.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(9, 10))]
+        #[transactional]
+        pub fn sell_to_order(origin: OriginFor<T>, order_id: OrderId, amount: u16) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let mut order = Self::orders(order_id).ok_or(Error::<T>::OrderNotFound)?;
+            ensure!(order.side == Side::Bid, Error::<T>::WrongOrderSide);
+            ensure!(who != order.owner, Error::<T>::CannotBuyOwnCard);
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+            ensure!(order.amount >= amount, Error::<T>::NotEnoughCardsWanted);
+            ensure!(!order.is_expired(&<frame_system::Module<T>>::block_number()), Error::<T>::OrderExpired);
+
+            if Self::fill_order(order_id, &mut order, &who, amount, None)? {
+                Self::remove_from_book(order.card_id, order.side, order_id);
+            }
+            Ok(().into())
+        }
+
+        /// Buys up to `amount` copies of `card_id` from the cheapest sell orders.
+        ///
+        /// Trades with at most `MaxFillsPerCall` orders and fails if the copies would cost
+        /// more than `max_total` altogether.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+            2 + 9 * T::MaxFillsPerCall::get() as Weight, 1 + 9 * T::MaxFillsPerCall::get() as Weight))]
+        #[transactional]
+        pub fn market_buy(origin: OriginFor<T>, card_id: CardId, amount: u16, max_total: T::Balance)
+            -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+
+            let now = <frame_system::Module<T>>::block_number();
+            let mut book = Self::asks(card_id);
+            let (mut index, mut fills, mut bought, mut spent) = (0, 0, 0u16, T::Balance::zero());
+            while bought < amount && fills < T::MaxFillsPerCall::get() {
+                let (_, maker_id) = match book.get(index) {
+                    Some(entry) => *entry,
+                    None => break,
+                };
+                let mut maker = Self::orders(maker_id).ok_or(Error::<T>::OrderNotFound)?;
+                if maker.owner == who || maker.is_expired(&now) {
+                    index += 1;
+                    continue;
+                }
+                let fill = (amount - bought).min(maker.amount);
+                spent = spent.saturating_add(maker.price.saturating_mul(T::Balance::from(fill)));
+                if Self::fill_order(maker_id, &mut maker, &who, fill, None)? {
+                    book.remove(index);
+                } else {
+                    index += 1;
+                }
+                bought += fill;
+                fills += 1;
+            }
+            ensure!(bought > 0, Error::<T>::NotEnoughCardsForSale);
+            ensure!(spent <= max_total, Error::<T>::PriceAboveMaximum);
+            Self::put_book(card_id, Side::Ask, book);
+
+            Ok(().into())
+        }
+
+        /// Offers `counterparty` the cards in `give` plus `give_balance` for the cards in `take`
+        /// plus `take_balance`.
+        ///
+        /// Everything the proposer gives is reserved, along with `TradeDeposit`, until the offer
+        /// is accepted, cancelled or expires in block `expires`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+            3 + 2 * give.len() as Weight, 4 + give.len() as Weight))]
+        #[transactional]
+        pub fn propose_trade(origin: OriginFor<T>, counterparty: T::AccountId, give: Vec<(CardId, u16)>,
+            take: Vec<(CardId, u16)>, give_balance: T::Balance, take_balance: T::Balance,
+            expires: Option<T::BlockNumber>) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(who != counterparty, Error::<T>::CannotTradeWithSelf);
+            ensure!(!(give.is_empty() && take.is_empty()), Error::<T>::EmptyTrade);
+            Self::ensure_trade_items(&give)?;
+            Self::ensure_trade_items(&take)?;
+            if let Some(expires) = expires {
+                ensure!(expires > <frame_system::Module<T>>::block_number(), Error::<T>::InvalidExpiry);
+            }
+
+            let trade_id = Self::next_trade_id();
+            let next_id = trade_id.checked_add(1).ok_or(Error::<T>::TradeIdOverflow)?;
+            for (card_id, amount) in &give {
+                Self::reserve_cards(&who, *card_id, *amount)?;
+            }
+            let deposit = T::TradeDeposit::get();
+            T::Currency::reserve(&who, give_balance.saturating_add(deposit)).map_err(|_| Error::<T>::InsufficientBalance)?;
+
+            <Trades<T>>::insert(trade_id, TradeOffer {
+                proposer: who.clone(),
+                counterparty: counterparty.clone(),
+                give,
+                take,
+                give_balance,
+                take_balance,
+                deposit,
+                expires,
+            });
+            if let Some(expires) = expires {
+                <TradesExpiringAt<T>>::append(expires, trade_id);
+            }
+            <NextTradeId<T>>::put(next_id);
+
+            Self::deposit_event(Event::TradeProposed(trade_id, who, counterparty));
+            Ok(().into())
+        }
+
+        /// Accepts a trade offer, either everything in it changes hands or nothing does.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+            4 + 4 * T::MaxTradeItems::get() as Weight, 4 + 6 * T::MaxTradeItems::get() as Weight))]
+        #[transactional]
+        pub fn accept_trade(origin: OriginFor<T>, trade_id: TradeId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let trade = <Trades<T>>::take(trade_id).ok_or(Error::<T>::TradeNotFound)?;
+            ensure!(trade.counterparty == who, Error::<T>::NoPermission);
+            ensure!(!trade.is_expired(&<frame_system::Module<T>>::block_number()), Error::<T>::TradeExpired);
+
+            for (card_id, amount) in &trade.give {
+                Self::unreserve_cards(&trade.proposer, *card_id, *amount);
+                Self::do_transfer(&trade.proposer, &who, *card_id, *amount)?;
+                Self::deposit_event(Event::CardTransferred(trade.proposer.clone(), *card_id, who.clone(), *amount));
+            }
+            for (card_id, amount) in &trade.take {
+                Self::do_transfer(&who, &trade.proposer, *card_id, *amount)?;
+                Self::deposit_event(Event::CardTransferred(who.clone(), *card_id, trade.proposer.clone(), *amount));
+            }
+            T::Currency::repatriate_reserved(&trade.proposer, &who, trade.give_balance, BalanceStatus::Free)?;
+            T::Currency::unreserve(&trade.proposer, trade.deposit);
+            if !trade.take_balance.is_zero() {
+                let required = trade.take_balance.saturating_add(T::Currency::minimum_balance());
+                ensure!(T::Currency::free_balance(&who) >= required, Error::<T>::InsufficientBalance);
+                T::Currency::transfer(&who, &trade.proposer, trade.take_balance, ExistenceRequirement::KeepAlive)?;
+            }
+            Self::unschedule_trade_expiry(trade_id, trade.expires);
+
+            Self::deposit_event(Event::TradeAccepted(trade_id));
+            Ok(().into())
+        }
+
+        /// Withdraws a trade offer, or declines it when called by the counterparty.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+            2 + T::MaxTradeItems::get() as Weight, 2 + T::MaxTradeItems::get() as Weight))]
+        pub fn cancel_trade(origin: OriginFor<T>, trade_id: TradeId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let trade = Self::trades(trade_id).ok_or(Error::<T>::TradeNotFound)?;
+            ensure!(trade.proposer == who || trade.counterparty == who, Error::<T>::NoPermission);
+
+            <Trades<T>>::remove(trade_id);
+            Self::unschedule_trade_expiry(trade_id, trade.expires);
+            Self::release_trade(&trade);
+
+            Self::deposit_event(Event::TradeCancelled(trade_id));
+            Ok(().into())
         }
 
+        /// Changes the marketplace fee, in parts per thousand.
         #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
-		pub fn show_user_cards(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
-            todo!();
+        pub fn set_market_fee(origin: OriginFor<T>, fee: u16) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(fee <= 1_000, Error::<T>::InvalidFee);
+
+            <MarketFee<T>>::put(fee);
+            Self::deposit_event(Event::MarketFeeChanged(fee));
+            Ok(().into())
+        }
+
+        /// Offers `amount` copies of `card_id` for `price` each in `asset_id`, replacing a previous
+        /// such offer.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+        #[transactional]
+        pub fn set_card_for_asset_sale(origin: OriginFor<T>, card_id: CardId, asset_id: T::AssetId,
+            price: T::Balance, amount: u16) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(<CardOwners<T>>::contains_key(&who, card_id), Error::<T>::CardNotOwned);
+
+            if let Some(listing) = <AssetListings<T>>::take(card_id, &who) {
+                Self::unreserve_cards(&who, card_id, listing.amount);
+            }
+            Self::reserve_cards(&who, card_id, amount)?;
+            <AssetListings<T>>::insert(card_id, &who, AssetListing { asset_id, price, amount });
+
+            Self::deposit_event(Event::CardSetForAssetSale(card_id, who, asset_id, price, amount));
+            Ok(().into())
+        }
+
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+        pub fn remove_card_from_asset_sale(origin: OriginFor<T>, card_id: CardId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let listing = <AssetListings<T>>::take(card_id, &who).ok_or(Error::<T>::CardNotForSale)?;
+
+            Self::unreserve_cards(&who, card_id, listing.amount);
+
+            Self::deposit_event(Event::CardRemovedFromAssetSale(card_id, who));
+            Ok(().into())
+        }
+
+        /// Buys `amount` copies from an offer priced in an asset, paying in that asset.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 6))]
+        #[transactional]
+        pub fn buy_with_asset(origin: OriginFor<T>, card_id: CardId, card_owner: T::AccountId, amount: u16,
+            max_price: T::Balance) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let mut listing = Self::asset_listings(card_id, &card_owner).ok_or(Error::<T>::CardNotForSale)?;
+            ensure!(who != card_owner, Error::<T>::CannotBuyOwnCard);
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+            ensure!(listing.amount >= amount, Error::<T>::NotEnoughCardsForSale);
+            ensure!(listing.price <= max_price, Error::<T>::PriceAboveMaximum);
+
+            let total = listing.price.checked_mul(&T::Balance::from(amount)).ok_or(Error::<T>::PriceOverflow)?;
+            let asset_id = listing.asset_id;
+            ensure!(T::Assets::transferable_balance(asset_id, &who) >= total, Error::<T>::InsufficientBalance);
+            Self::split_payment(&card_owner, &who, card_id, total, |to, value| {
+                T::Assets::transfer(asset_id, &who, to, value)
+            })?;
+            Self::unreserve_cards(&card_owner, card_id, amount);
+            Self::do_transfer(&card_owner, &who, card_id, amount)?;
+            Self::deposit_event(Event::CardSoldForAsset(card_id, card_owner.clone(), who, amount, asset_id,
+                listing.price));
+
+            listing.amount -= amount;
+            if listing.amount == 0 {
+                <AssetListings<T>>::remove(card_id, &card_owner);
+            } else {
+                <AssetListings<T>>::insert(card_id, &card_owner, listing);
+            }
+            Ok(().into())
+        }
+
+        /// Offers `amount` copies of `card_id` for rent for `duration` blocks at `price` each,
+        /// replacing a previous such offer.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+        #[transactional]
+        pub fn offer_for_rent(origin: OriginFor<T>, card_id: CardId, price: T::Balance, duration: T::BlockNumber,
+            amount: u16) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(<CardOwners<T>>::contains_key(&who, card_id), Error::<T>::CardNotOwned);
+            ensure!(!duration.is_zero() && duration <= T::MaxRentalDuration::get(), Error::<T>::InvalidRentalDuration);
+
+            if let Some(offer) = <RentalOffers<T>>::take(card_id, &who) {
+                Self::unreserve_cards(&who, card_id, offer.amount);
+            }
+            Self::reserve_cards(&who, card_id, amount)?;
+            <RentalOffers<T>>::insert(card_id, &who, RentalOffer { price, duration, amount });
+
+            Self::deposit_event(Event::CardOfferedForRent(card_id, who, price, duration, amount));
+            Ok(().into())
+        }
+
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+        pub fn remove_rental_offer(origin: OriginFor<T>, card_id: CardId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let offer = <RentalOffers<T>>::take(card_id, &who).ok_or(Error::<T>::CardNotForSale)?;
+
+            Self::unreserve_cards(&who, card_id, offer.amount);
+
+            Self::deposit_event(Event::RentalOfferRemoved(card_id, who));
+            Ok(().into())
+        }
+
+        /// Rents `amount` copies from an offer of `card_owner`.
+        ///
+        /// The renter gets usage rights for the term of the offer, the copies stay reserved in
+        /// the owner's collection and return to the owner when the term ends.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 8))]
+        #[transactional]
+        pub fn rent(origin: OriginFor<T>, card_id: CardId, card_owner: T::AccountId, amount: u16,
+            max_price: T::Balance) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let mut offer = Self::rental_offers(card_id, &card_owner).ok_or(Error::<T>::CardNotForSale)?;
+            ensure!(who != card_owner, Error::<T>::CannotBuyOwnCard);
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+            ensure!(offer.amount >= amount, Error::<T>::NotEnoughCardsForSale);
+            ensure!(offer.price <= max_price, Error::<T>::PriceAboveMaximum);
+
+            let total = offer.price.checked_mul(&T::Balance::from(amount)).ok_or(Error::<T>::PriceOverflow)?;
+            let required = total.saturating_add(T::Currency::minimum_balance());
+            ensure!(T::Currency::free_balance(&who) >= required, Error::<T>::InsufficientBalance);
+            T::Currency::transfer(&who, &card_owner, total, ExistenceRequirement::KeepAlive)?;
+
+            let rental_id = Self::next_rental_id();
+            <NextRentalId<T>>::put(rental_id.checked_add(1).ok_or(Error::<T>::RentalIdOverflow)?);
+            let end = <frame_system::Module<T>>::block_number().saturating_add(offer.duration);
+            // the rented copies stay reserved, they now count as lent instead of offered
+            <LentCards<T>>::mutate(&card_owner, card_id, |lent| *lent = lent.saturating_add(amount));
+            <UsageRights<T>>::mutate(&who, card_id, |rights| *rights = rights.saturating_add(amount));
+            <Rentals<T>>::insert(rental_id, Rental { owner: card_owner.clone(), renter: who.clone(), card_id, amount, end });
+            <RentalsEndingAt<T>>::append(end, rental_id);
+
+            offer.amount -= amount;
+            if offer.amount == 0 {
+                <RentalOffers<T>>::remove(card_id, &card_owner);
+            } else {
+                <RentalOffers<T>>::insert(card_id, &card_owner, offer);
+            }
+
+            Self::deposit_event(Event::CardRented(rental_id, card_id, card_owner, who, amount, end));
+            Ok(().into())
+        }
+
+        /// Registers a set of up to `size` cards released in block `release`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+        pub fn create_set(origin: OriginFor<T>, code: Vec<u8>, name: Vec<u8>, release: T::BlockNumber, size: u32)
+            -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(<CreatorRegistry<T>>::contains_key(&who), Error::<T>::NoPermission);
+            ensure!(!code.is_empty() && code.len() <= T::MaxSetCodeLength::get() as usize
+                && code.iter().all(u8::is_ascii_alphanumeric), Error::<T>::InvalidSetCode);
+            ensure!(!<SetCodes<T>>::contains_key(&code), Error::<T>::SetCodeTaken);
+            ensure!(name.len() <= T::MaxNameLength::get() as usize, Error::<T>::NameTooLong);
+            Self::ensure_text(&name)?;
+            ensure!(size > 0 && size <= T::MaxSetSize::get(), Error::<T>::InvalidSetSize);
+
+            let set_id = Self::next_set_id();
+            <NextSetId<T>>::put(set_id.checked_add(1).ok_or(Error::<T>::SetIdOverflow)?);
+            <SetCodes<T>>::insert(&code, set_id);
+            <Sets<T>>::insert(set_id, CardSet { creator: who.clone(), code: code.clone(), name, release, size, closed: false });
+
+            Self::deposit_event(Event::SetCreated(set_id, who, code));
+            Ok(().into())
+        }
+
+        /// Creates `amount` copies of a new card as number `collector_number` of set `set_id`.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 8))]
+        #[transactional]
+        pub fn create_card_in_set(origin: OriginFor<T>, set_id: SetId, collector_number: u32, card: Card,
+            amount: u16, royalty: u16, max_supply: Option<u32>) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(<CreatorRegistry<T>>::contains_key(&who), Error::<T>::NoPermission);
+            let set = Self::sets(set_id).ok_or(Error::<T>::SetNotFound)?;
+            ensure!(set.creator == who, Error::<T>::NoPermission);
+            ensure!(!set.closed, Error::<T>::SetClosed);
+            ensure!(collector_number > 0 && collector_number <= set.size, Error::<T>::InvalidCollectorNumber);
+
+            let mut cards = Self::set_cards(set_id);
+            let index = match cards.binary_search_by_key(&collector_number, |(number, _)| *number) {
+                Ok(_) => Err(Error::<T>::CollectorNumberTaken)?,
+                Err(index) => index,
+            };
+            let card_id = Self::do_create_card(who, card, amount, royalty, max_supply)?;
+            cards.insert(index, (collector_number, card_id));
+            <SetCards<T>>::insert(set_id, cards);
+            <CardPrintings<T>>::insert(card_id, (set_id, collector_number));
+
+            Self::deposit_event(Event::CardAddedToSet(set_id, card_id, collector_number));
+            Ok(().into())
+        }
+
+        /// Stops adding cards to a set.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+        pub fn close_set(origin: OriginFor<T>, set_id: SetId) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let mut set = Self::sets(set_id).ok_or(Error::<T>::SetNotFound)?;
+            ensure!(set.creator == who, Error::<T>::NoPermission);
+            ensure!(!set.closed, Error::<T>::SetClosed);
+
+            set.closed = true;
+            <Sets<T>>::insert(set_id, set);
+            Self::deposit_event(Event::SetClosed(set_id));
+            Ok(().into())
+        }
+
+        /// Replaces the rules and stats of a card, keeping the previous ones in `CardHistory`.
+        ///
+        /// Callable by the creator of the card or by root until the card is frozen. Only the
+        /// latest `MaxErrata` previous versions are kept.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 2))]
+        pub fn issue_errata(origin: OriginFor<T>, card_id: CardId, rules: Vec<u8>, keywords: Keywords,
+            power_toughness: Option<PowerToughness>, loyalty: Option<u8>) -> DispatchResultWithPostInfo {
+            Self::ensure_creator_or_root(origin, card_id)?;
+            let mut card = Self::cards(card_id).ok_or(Error::<T>::UnknownCard)?;
+            ensure!(!Self::is_frozen(card_id), Error::<T>::CardIsFrozen);
+            let mut history = Self::card_history(card_id);
+
+            ensure!(rules.len() <= T::MaxRulesLength::get() as usize, Error::<T>::RulesTooLong);
+            Self::ensure_text(&rules)?;
+            Self::ensure_stats(&card.type_line.types, &power_toughness, &loyalty)?;
+
+            history.push(Erratum {
+                rules: sp_std::mem::replace(&mut card.rules, rules),
+                keywords: sp_std::mem::replace(&mut card.keywords, keywords),
+                power_toughness: sp_std::mem::replace(&mut card.power_toughness, power_toughness),
+                loyalty: sp_std::mem::replace(&mut card.loyalty, loyalty),
+                replaced: <frame_system::Module<T>>::block_number(),
+            });
+            if history.len() > T::MaxErrata::get() as usize {
+                history.remove(0);
+            }
+            let errata = history.len() as u32;
+            <CardRegistry<T>>::insert(card_id, card);
+            <CardHistory<T>>::insert(card_id, history);
+
+            Self::deposit_event(Event::CardErrata(card_id, errata));
+            Ok(().into())
+        }
+
+        /// Forbids any further errata of a card.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 1))]
+        pub fn freeze_card(origin: OriginFor<T>, card_id: CardId) -> DispatchResultWithPostInfo {
+            Self::ensure_creator_or_root(origin, card_id)?;
+            ensure!(<CardRegistry<T>>::contains_key(card_id), Error::<T>::UnknownCard);
+            ensure!(!Self::is_frozen(card_id), Error::<T>::CardIsFrozen);
+
+            <FrozenCards<T>>::insert(card_id, true);
+            Self::deposit_event(Event::CardFrozen(card_id));
+            Ok(().into())
+        }
+
+        /// Mints `amount` more copies of `card_id` to `to`. Only the creator of the card can.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(4, 2))]
+        pub fn mint_more(origin: OriginFor<T>, card_id: CardId, to: T::AccountId, amount: u16)
+            -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let info = Self::card_info(card_id).ok_or(Error::<T>::UnknownCard)?;
+            ensure!(info.creator == who, Error::<T>::NoPermission);
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+            let rarity = Self::cards(card_id).ok_or(Error::<T>::UnknownCard)?.rarity;
+            ensure!(amount <= Self::max_pack(rarity), Error::<T>::PackTooLarge);
+
+            let mut supply = Self::supply(card_id);
+            supply.minted = supply.minted.checked_add(amount.into()).ok_or(Error::<T>::SupplyExceeded)?;
+            ensure!(supply.max.map_or(true, |max| supply.minted <= max), Error::<T>::SupplyExceeded);
+            let owned = Self::owners(&to, card_id).unwrap_or(0).checked_add(amount).ok_or(Error::<T>::TooManyCopies)?;
+
+            <CardSupplies<T>>::insert(card_id, supply);
+            <CardOwners<T>>::insert(&to, card_id, owned);
+            Self::deposit_event(Event::CardsMinted(card_id, to, amount));
             Ok(().into())
         }
 
 	}
-}
\ No newline at end of file
+
+    impl<T: Config> Pallet<T> {
+        /// Copies of `card_id` owned by `who` that are not reserved.
+        pub fn free_cards(who: &T::AccountId, card_id: CardId) -> u16 {
+            Self::owners(who, card_id).unwrap_or(0)
+                .saturating_sub(Self::reserved_cards(who, card_id))
+        }
+
+        fn do_create_card(who: T::AccountId, card: Card, amount: u16, royalty: u16, max_supply: Option<u32>)
+            -> Result<CardId, DispatchError> {
+            ensure!(royalty <= T::MaxRoyalty::get(), Error::<T>::RoyaltyTooHigh);
+            Self::ensure_card(&card)?;
+            ensure!(amount <= Self::max_pack(card.rarity), Error::<T>::PackTooLarge);
+            ensure!(max_supply.map_or(true, |max| u32::from(amount) <= max), Error::<T>::SupplyExceeded);
+            let rarity = card.rarity;
+
+            let id = Self::previous_card_id().checked_add(1).ok_or(Error::<T>::IdStorageOverflow)?;
+            <CardRegistry<T>>::insert(id, card);
+            <PreviousCardId<T>>::put(id);
+            <CardRoyalties<T>>::insert(id, royalty);
+            <CardInfos<T>>::insert(id, CardInfo {
+                creator: who.clone(),
+                created: <frame_system::Module<T>>::block_number(),
+                timestamp: <pallet_timestamp::Module<T>>::get(),
+                initial_supply: amount,
+            });
+            <CardSupplies<T>>::insert(id, Supply { minted: amount.into(), max: max_supply });
+            <CardOwners<T>>::insert(&who, id, amount);
+
+            Self::deposit_event(Event::CardCreated(who, id, amount, rarity));
+            Ok(id)
+        }
+
+        /// Most copies of a card of `rarity` created at once.
+        pub fn max_pack(rarity: Rarity) -> u16 {
+            match rarity {
+                Rarity::Common => T::MaxCommonPack::get(),
+                Rarity::Uncommon => T::MaxUncommonPack::get(),
+                Rarity::Rare => T::MaxRarePack::get(),
+                Rarity::Mythic => T::MaxMythicPack::get(),
+                Rarity::Promo => T::MaxPromoPack::get(),
+            }
+        }
+
+        /// Checks the text of a card against the configured lengths and its parts against each other.
+        fn ensure_card(card: &Card) -> DispatchResult {
+            ensure!(card.color.is_valid(), Error::<T>::InvalidColor);
+            ensure!(card.name.len() <= T::MaxNameLength::get() as usize, Error::<T>::NameTooLong);
+            ensure!(card.rules.len() <= T::MaxRulesLength::get() as usize, Error::<T>::RulesTooLong);
+            Self::ensure_text(&card.name)?;
+            Self::ensure_text(&card.rules)?;
+            Self::ensure_type_line(&card.type_line)?;
+            Self::ensure_mana_cost(&card.mana_cost)?;
+            // a cost without colored symbols leaves the color to the card, e.g. for lands
+            let cost_colors = card.mana_cost.color_identity();
+            ensure!(cost_colors == COLORLESS || cost_colors == card.color, Error::<T>::ColorMismatch);
+            Self::ensure_stats(&card.type_line.types, &card.power_toughness, &card.loyalty)
+        }
+
+        /// Ensures only creatures have, and must have, power and toughness and only planeswalkers
+        /// have loyalty.
+        fn ensure_stats(types: &[CardType], power_toughness: &Option<PowerToughness>, loyalty: &Option<u8>)
+            -> DispatchResult {
+            ensure!(types.contains(&CardType::Creature) == power_toughness.is_some(), Error::<T>::InvalidStats);
+            ensure!(types.contains(&CardType::Planeswalker) || loyalty.is_none(), Error::<T>::InvalidStats);
+            Ok(())
+        }
+
+        /// Ensures `origin` is root or the creator of `card_id`.
+        fn ensure_creator_or_root(origin: OriginFor<T>, card_id: CardId) -> DispatchResult {
+            if let Ok(who) = ensure_signed(origin.clone()) {
+                let info = Self::card_info(card_id).ok_or(Error::<T>::UnknownCard)?;
+                ensure!(info.creator == who, Error::<T>::NoPermission);
+            } else {
+                ensure_root(origin)?;
+            }
+            Ok(())
+        }
+
+        fn ensure_mana_cost(cost: &ManaCost) -> DispatchResult {
+            ensure!(cost.pips.iter().all(|pip| pip.is_valid()), Error::<T>::InvalidManaPip);
+            ensure!(cost.pips.len() <= T::MaxManaPips::get() as usize, Error::<T>::TooManyManaPips);
+            Ok(())
+        }
+
+        fn ensure_type_line(line: &TypeLine) -> DispatchResult {
+            ensure!(!line.types.is_empty(), Error::<T>::InvalidTypeLine);
+            // bound the subtypes before comparing them with each other
+            ensure!(line.subtypes.len() <= T::MaxSubtypes::get() as usize, Error::<T>::TooManySubtypes);
+            for subtype in &line.subtypes {
+                ensure!(!subtype.is_empty() && subtype.len() <= T::MaxNameLength::get() as usize,
+                    Error::<T>::InvalidTypeLine);
+                Self::ensure_text(subtype)?;
+            }
+            ensure!(!has_repeats(&line.types) && !has_repeats(&line.supertypes) && !has_repeats(&line.subtypes),
+                Error::<T>::InvalidTypeLine);
+            Ok(())
+        }
+
+        /// Ensures `text` is UTF-8 without control characters.
+        fn ensure_text(text: &[u8]) -> DispatchResult {
+            let text = sp_std::str::from_utf8(text).map_err(|_| Error::<T>::InvalidEncoding)?;
+            ensure!(!text.chars().any(char::is_control), Error::<T>::InvalidEncoding);
+            Ok(())
+        }
+
+        /// Copies of `card_id` `who` may play with: owned copies that are not rented out and
+        /// rented copies.
+        pub fn usable_cards(who: &T::AccountId, card_id: CardId) -> u16 {
+            Self::owners(who, card_id).unwrap_or(0)
+                .saturating_sub(Self::lent_cards(who, card_id))
+                .saturating_add(Self::usage_rights(who, card_id))
+        }
+
+        /// Reserves `amount` free copies of `card_id` owned by `who`.
+        pub(crate) fn reserve_cards(who: &T::AccountId, card_id: CardId, amount: u16) -> DispatchResult {
+            Self::ensure_free_cards(who, card_id, amount)?;
+            <ReservedCards<T>>::mutate(who, card_id, |reserved| *reserved = reserved.saturating_add(amount));
+            Ok(())
+        }
+
+        /// Releases up to `amount` reserved copies of `card_id` owned by `who`.
+        pub(crate) fn unreserve_cards(who: &T::AccountId, card_id: CardId, amount: u16) {
+            <ReservedCards<T>>::mutate_exists(who, card_id, |reserved| {
+                let left = reserved.unwrap_or(0).saturating_sub(amount);
+                *reserved = if left == 0 { None } else { Some(left) };
+            });
+        }
+
+        fn ensure_free_cards(who: &T::AccountId, card_id: CardId, amount: u16) -> DispatchResult {
+            ensure!(amount > 0, Error::<T>::ZeroAmount);
+            let owned = Self::owners(who, card_id).ok_or(Error::<T>::CardNotOwned)?;
+            ensure!(owned >= amount, Error::<T>::NotEnoughCards);
+            ensure!(Self::free_cards(who, card_id) >= amount, Error::<T>::CardsReserved);
+            Ok(())
+        }
+
+        /// Hands the lot to the best revealed bidder at the second best price and settles the deposits.
+        fn settle_sealed_auction(auction_id: AuctionId, auction: SealedAuction<T::AccountId, T::Balance, T::BlockNumber>) {
+
+            let winner = auction.best_bid.and_then(|(bidder, value)| {
+                let price = auction.second_bid.unwrap_or(auction.reserve_price).max(auction.reserve_price);
+                T::Currency::unreserve(&bidder, value.saturating_sub(price));
+                match Self::settle_lot(&auction.seller, &bidder, auction.card_id, auction.amount, price) {
+                    Ok(()) => Some((bidder, price)),
+                    Err(_) => {
+                        T::Currency::unreserve(&bidder, price);
+                        None
+                    },
+                }
+            });
+            if winner.is_none() {
+                Self::unreserve_cards(&auction.seller, auction.card_id, auction.amount);
+            }
+
+            for (bidder, bid) in <SealedBids<T>>::drain_prefix(auction_id) {
+                if bid.revealed.is_some() {
+                    T::Currency::unreserve(&bidder, bid.deposit);
+                } else {
+                    let (_, not_slashed) = T::Currency::slash_reserved(&bidder, bid.deposit);
+                    Self::deposit_event(Event::BidDepositSlashed(auction_id, bidder, bid.deposit.saturating_sub(not_slashed)));
+                }
+            }
+            Self::deposit_event(Event::AuctionSettled(auction_id, winner));
+        }
+
+        /// Sells an auction lot whose copies and price are both reserved. A failed settlement,
+        /// e.g. because a payee can not receive funds, leaves the storage untouched.
+        #[transactional]
+        fn settle_lot(seller: &T::AccountId, buyer: &T::AccountId, card_id: CardId, amount: u16, price: T::Balance)
+            -> DispatchResult {
+            Self::settle_trade(seller, buyer, card_id, amount, price, true, true)
+        }
+
+        /// Moves `amount` copies of `card_id` from `seller` to `buyer` for `total`.
+        ///
+        /// `cards_reserved` and `funds_reserved` tell whether the copies and the payment are
+        /// reserved for this trade or have to be taken from what is free.
+        fn settle_trade(seller: &T::AccountId, buyer: &T::AccountId, card_id: CardId, amount: u16, total: T::Balance,
+            cards_reserved: bool, funds_reserved: bool) -> DispatchResult {
+            if !funds_reserved {
+                let required = total.saturating_add(T::Currency::minimum_balance());
+                ensure!(T::Currency::free_balance(buyer) >= required, Error::<T>::InsufficientBalance);
+            }
+            Self::split_payment(seller, buyer, card_id, total, |to, value| Self::pay(buyer, to, value, funds_reserved))?;
+            if cards_reserved {
+                Self::unreserve_cards(seller, card_id, amount);
+            }
+            Self::do_transfer(seller, buyer, card_id, amount)?;
+
+            let price = total / T::Balance::from(amount);
+            Self::record_sale(seller, buyer, card_id, amount, price, total);
+            Self::deposit_event(Event::CardSold(card_id, seller.clone(), buyer.clone(), amount, price));
+            Ok(())
+        }
+
+        /// Updates the statistics of `card_id` with a sale of `amount` copies for `price` each.
+        fn record_sale(seller: &T::AccountId, buyer: &T::AccountId, card_id: CardId, amount: u16, price: T::Balance,
+            total: T::Balance) {
+            let now = <frame_system::Module<T>>::block_number();
+            <CardStats<T>>::mutate(card_id, |stats| {
+                let stats = stats.get_or_insert_with(|| MarketStats {
+                    last_price: price,
+                    last_sale: now,
+                    high: price,
+                    low: price,
+                    volume: Zero::zero(),
+                    copies_sold: 0,
+                });
+                stats.last_price = price;
+                stats.last_sale = now;
+                stats.high = stats.high.max(price);
+                stats.low = stats.low.min(price);
+                stats.volume = stats.volume.saturating_add(total);
+                stats.copies_sold = stats.copies_sold.saturating_add(amount.into());
+            });
+
+            <RecentTrades<T>>::mutate(card_id, |trades| {
+                if trades.len() >= T::MaxRecentTrades::get() as usize {
+                    trades.remove(0);
+                }
+                trades.push(SaleRecord { seller: seller.clone(), buyer: buyer.clone(), amount, price, block: now });
+            });
+
+            let (slot, start) = Self::volume_bucket(now);
+            <VolumeHistory<T>>::mutate(card_id, slot, |(bucket, volume)| {
+                if *bucket != start {
+                    *bucket = start;
+                    *volume = Zero::zero();
+                }
+                *volume = volume.saturating_add(total);
+            });
+        }
+
+        /// Balance paid for `card_id` in the current and the previous `VolumeBuckets - 1` buckets,
+        /// i.e. roughly the last `VolumeWindow` blocks.
+        pub fn rolling_volume(card_id: CardId) -> T::Balance {
+            let (_, start) = Self::volume_bucket(<frame_system::Module<T>>::block_number());
+            let window = Self::volume_bucket_length().saturating_mul(Self::volume_buckets());
+            <VolumeHistory<T>>::iter_prefix_values(card_id)
+                .filter(|(bucket, _)| (*bucket).saturating_add(window) > start)
+                .fold(Zero::zero(), |sum: T::Balance, (_, volume)| sum.saturating_add(volume))
+        }
+
+        /// Slot in `VolumeHistory` and first block of the volume bucket of `block`.
+        fn volume_bucket(block: T::BlockNumber) -> (u32, T::BlockNumber) {
+            let length = Self::volume_bucket_length();
+            let slot = (block / length) % Self::volume_buckets();
+            (slot.unique_saturated_into(), block - block % length)
+        }
+
+        fn volume_bucket_length() -> T::BlockNumber {
+            (T::VolumeWindow::get() / Self::volume_buckets()).max(One::one())
+        }
+
+        fn volume_buckets() -> T::BlockNumber {
+            T::BlockNumber::from(T::VolumeBuckets::get().max(1))
+        }
+
+        /// Pays `total` for copies of `card_id` with `pay`: the marketplace fee and the creator's
+        /// royalty first, the rest to the seller.
+        fn split_payment(seller: &T::AccountId, buyer: &T::AccountId, card_id: CardId, total: T::Balance,
+            mut pay: impl FnMut(&T::AccountId, T::Balance) -> DispatchResult) -> DispatchResult {
+            let mut proceeds = total;
+            let fee = Permill::from_perthousand(Self::market_fee().into()) * total;
+            if !fee.is_zero() {
+                pay(&T::FeeDestination::get(), fee)?;
+                proceeds = proceeds.saturating_sub(fee);
+            }
+            // The creator gets nothing from their own sales. A creator who can not receive the
+            // royalty, e.g. because their account was reaped, leaves it to the seller.
+            let creator = Self::card_info(card_id).map(|info| info.creator)
+                .filter(|creator| creator != seller && creator != buyer);
+            if let Some(creator) = creator {
+                let share = (Permill::from_perthousand(Self::royalty(card_id).into()) * total).min(proceeds);
+                if !share.is_zero() && pay(&creator, share).is_ok() {
+                    proceeds = proceeds.saturating_sub(share);
+                    Self::deposit_event(Event::RoyaltyPaid(card_id, creator, share));
+                }
+            }
+            pay(seller, proceeds)
+        }
+
+        /// Moves `value` from the reserved or the free balance of `from` to `to`.
+        fn pay(from: &T::AccountId, to: &T::AccountId, value: T::Balance, reserved: bool) -> DispatchResult {
+            if reserved {
+                T::Currency::repatriate_reserved(from, to, value, BalanceStatus::Free)?;
+            } else {
+                T::Currency::transfer(from, to, value, ExistenceRequirement::KeepAlive)?;
+            }
+            Ok(())
+        }
+
+        /// Reserves what a new order offers, trades it against the opposite side of the book
+        /// and rests the remainder in the book.
+        fn place_order(mut order: Order<T::AccountId, T::Balance, T::BlockNumber>) -> Result<OrderId, DispatchError> {
+            ensure!(order.amount > 0, Error::<T>::ZeroAmount);
+            ensure!(!order.price.is_zero(), Error::<T>::ZeroPrice);
+            if let Some(expires) = order.expires {
+                ensure!(expires > <frame_system::Module<T>>::block_number(), Error::<T>::InvalidExpiry);
+            }
+            match order.side {
+                Side::Ask => Self::reserve_cards(&order.owner, order.card_id, order.amount)?,
+                Side::Bid => {
+                    ensure!(<CardRegistry<T>>::contains_key(order.card_id), Error::<T>::UnknownCard);
+                    let total = order.price.checked_mul(&T::Balance::from(order.amount))
+                        .ok_or(Error::<T>::PriceOverflow)?;
+                    T::Currency::reserve(&order.owner, total).map_err(|_| Error::<T>::InsufficientBalance)?;
+                },
+            }
+
+            let order_id = Self::next_order_id();
+            <NextOrderId<T>>::put(order_id.checked_add(1).ok_or(Error::<T>::OrderIdOverflow)?);
+            Self::deposit_event(Event::OrderPlaced(order_id, order.owner.clone(), order.card_id, order.side,
+                order.price, order.amount));
+
+            Self::match_order(&mut order)?;
+            if order.amount > 0 {
+                Self::insert_order(order_id, order)?;
+            }
+            Ok(order_id)
+        }
+
+        /// Trades a new order with the best crossing orders that have not expired, at most
+        /// `MaxFillsPerCall` of them.
+        fn match_order(order: &mut Order<T::AccountId, T::Balance, T::BlockNumber>) -> DispatchResult {
+            let side = order.side.opposite();
+            let now = <frame_system::Module<T>>::block_number();
+            let mut book = Self::book(order.card_id, side);
+            let (mut index, mut fills) = (0, 0);
+            while order.amount > 0 && fills < T::MaxFillsPerCall::get() {
+                let (price, maker_id) = match book.get(index) {
+                    Some(entry) => *entry,
+                    None => break,
+                };
+                if !order.crosses(&price) {
+                    break;
+                }
+                let mut maker = Self::orders(maker_id).ok_or(Error::<T>::OrderNotFound)?;
+                if maker.owner == order.owner || maker.is_expired(&now) {
+                    index += 1;
+                    continue;
+                }
+                let amount = order.amount.min(maker.amount);
+                if Self::fill_order(maker_id, &mut maker, &order.owner, amount, Some(order.price))? {
+                    book.remove(index);
+                } else {
+                    index += 1;
+                }
+                order.amount -= amount;
+                fills += 1;
+            }
+            Self::put_book(order.card_id, side, book);
+            Ok(())
+        }
+
+        /// Trades `amount` copies of the resting order `maker_id` with `taker` at the resting price.
+        ///
+        /// `taker_price` is the price of the taker's own crossing order, which already reserves
+        /// the taker's side of the trade. Returns whether the resting order is used up and removed,
+        /// its entry in the book is left to the caller.
+        fn fill_order(maker_id: OrderId, maker: &mut Order<T::AccountId, T::Balance, T::BlockNumber>,
+            taker: &T::AccountId, amount: u16, taker_price: Option<T::Balance>) -> Result<bool, DispatchError> {
+            let total = maker.price.saturating_mul(T::Balance::from(amount));
+            match maker.side {
+                Side::Ask => {
+                    Self::settle_trade(&maker.owner, taker, maker.card_id, amount, total, true, taker_price.is_some())?;
+                    if let Some(price) = taker_price {
+                        let change = price.saturating_sub(maker.price).saturating_mul(T::Balance::from(amount));
+                        T::Currency::unreserve(taker, change);
+                    }
+                },
+                Side::Bid => {
+                    Self::settle_trade(taker, &maker.owner, maker.card_id, amount, total, taker_price.is_some(), true)?;
+                },
+            }
+
+            maker.amount -= amount;
+            Self::deposit_event(Event::OrderFilled(maker_id, taker.clone(), amount, maker.price));
+            if maker.amount == 0 {
+                <Orders<T>>::remove(maker_id);
+                Self::unschedule_expiry(maker_id, maker.expires);
+                if maker.side == Side::Ask {
+                    Self::deposit_event(Event::CardRemovedFromSale(maker.card_id, maker.owner.clone()));
+                }
+                Ok(true)
+            } else {
+                <Orders<T>>::insert(maker_id, &*maker);
+                Ok(false)
+            }
+        }
+
+        /// Rests an order in the book. A full book drops its worst order for a better one.
+        fn insert_order(order_id: OrderId, order: Order<T::AccountId, T::Balance, T::BlockNumber>) -> DispatchResult {
+            let mut book = Self::book(order.card_id, order.side);
+            if book.len() as u32 >= T::MaxOrdersPerBook::get() {
+                let (worst_price, worst_id) = book.pop().ok_or(Error::<T>::OrderBookFull)?;
+                let better = match order.side {
+                    Side::Ask => order.price < worst_price,
+                    Side::Bid => order.price > worst_price,
+                };
+                ensure!(better, Error::<T>::OrderBookFull);
+                if let Some(worst) = Self::orders(worst_id) {
+                    Self::remove_order(worst_id, &worst);
+                }
+                Self::deposit_event(Event::OrderEvicted(worst_id));
+            }
+
+            let index = match order.side {
+                Side::Ask => book.iter().position(|(price, _)| *price > order.price),
+                Side::Bid => book.iter().position(|(price, _)| *price < order.price),
+            }.unwrap_or(book.len());
+            book.insert(index, (order.price, order_id));
+            Self::put_book(order.card_id, order.side, book);
+
+            if let Some(expires) = order.expires {
+                <OrdersExpiringAt<T>>::append(expires, order_id);
+            }
+            if order.side == Side::Ask {
+                Self::deposit_event(Event::CardSetForSale(order.card_id, order.owner.clone(), order.price, order.amount));
+            }
+            <Orders<T>>::insert(order_id, order);
+            Ok(())
+        }
+
+        /// Takes an order out of the book and releases what it still reserves.
+        fn remove_order(order_id: OrderId, order: &Order<T::AccountId, T::Balance, T::BlockNumber>) {
+            <Orders<T>>::remove(order_id);
+            Self::remove_from_book(order.card_id, order.side, order_id);
+            Self::unschedule_expiry(order_id, order.expires);
+            match order.side {
+                Side::Ask => {
+                    Self::unreserve_cards(&order.owner, order.card_id, order.amount);
+                    Self::deposit_event(Event::CardRemovedFromSale(order.card_id, order.owner.clone()));
+                },
+                Side::Bid => {
+                    T::Currency::unreserve(&order.owner, order.price.saturating_mul(T::Balance::from(order.amount)));
+                },
+            }
+        }
+
+        fn book(card_id: CardId, side: Side) -> Vec<(T::Balance, OrderId)> {
+            match side {
+                Side::Ask => Self::asks(card_id),
+                Side::Bid => Self::bids(card_id),
+            }
+        }
+
+        fn put_book(card_id: CardId, side: Side, book: Vec<(T::Balance, OrderId)>) {
+            match (side, book.is_empty()) {
+                (Side::Ask, true) => <Asks<T>>::remove(card_id),
+                (Side::Ask, false) => <Asks<T>>::insert(card_id, book),
+                (Side::Bid, true) => <Bids<T>>::remove(card_id),
+                (Side::Bid, false) => <Bids<T>>::insert(card_id, book),
+            }
+        }
+
+        fn remove_from_book(card_id: CardId, side: Side, order_id: OrderId) {
+            let mut book = Self::book(card_id, side);
+            book.retain(|(_, id)| *id != order_id);
+            Self::put_book(card_id, side, book);
+        }
+
+        fn unschedule_expiry(order_id: OrderId, expires: Option<T::BlockNumber>) {
+            if let Some(expires) = expires {
+                <OrdersExpiringAt<T>>::mutate_exists(expires, |ids| {
+                    if let Some(list) = ids {
+                        list.retain(|id| *id != order_id);
+                        if list.is_empty() {
+                            *ids = None;
+                        }
+                    }
+                });
+            }
+        }
+
+        fn ensure_trade_items(items: &[(CardId, u16)]) -> DispatchResult {
+            ensure!(items.len() as u32 <= T::MaxTradeItems::get(), Error::<T>::TooManyTradeItems);
+            for (index, (card_id, amount)) in items.iter().enumerate() {
+                ensure!(*amount > 0, Error::<T>::ZeroAmount);
+                ensure!(items[..index].iter().all(|(id, _)| id != card_id), Error::<T>::DuplicateTradeItem);
+            }
+            Ok(())
+        }
+
+        /// Returns rented copies to their owner.
+        fn end_rental(rental: &Rental<T::AccountId, T::BlockNumber>) {
+            <UsageRights<T>>::mutate_exists(&rental.renter, rental.card_id, |rights| {
+                let left = rights.unwrap_or(0).saturating_sub(rental.amount);
+                *rights = if left == 0 { None } else { Some(left) };
+            });
+            <LentCards<T>>::mutate_exists(&rental.owner, rental.card_id, |lent| {
+                let left = lent.unwrap_or(0).saturating_sub(rental.amount);
+                *lent = if left == 0 { None } else { Some(left) };
+            });
+            Self::unreserve_cards(&rental.owner, rental.card_id, rental.amount);
+        }
+
+        /// Releases the copies and balance a trade offer reserves.
+        fn release_trade(trade: &TradeOffer<T::AccountId, T::Balance, T::BlockNumber>) {
+            for (card_id, amount) in &trade.give {
+                Self::unreserve_cards(&trade.proposer, *card_id, *amount);
+            }
+            T::Currency::unreserve(&trade.proposer, trade.give_balance.saturating_add(trade.deposit));
+        }
+
+        fn unschedule_trade_expiry(trade_id: TradeId, expires: Option<T::BlockNumber>) {
+            if let Some(expires) = expires {
+                <TradesExpiringAt<T>>::mutate_exists(expires, |ids| {
+                    if let Some(list) = ids {
+                        list.retain(|id| *id != trade_id);
+                        if list.is_empty() {
+                            *ids = None;
+                        }
+                    }
+                });
+            }
+        }
+
+        /// Hands the lot to the best bidder and pays the seller, or returns the lot to the seller
+        /// and refunds the bid if there is no bid or the sale fails.
+        fn settle_auction(auction_id: AuctionId, auction: Auction<T::AccountId, T::Balance, T::BlockNumber>) {
+            let winner = auction.best_bid.filter(|(bidder, value)| {
+                let sold = Self::settle_lot(&auction.seller, bidder, auction.card_id, auction.amount, *value).is_ok();
+                if !sold {
+                    T::Currency::unreserve(bidder, *value);
+                }
+                sold
+            });
+            if winner.is_none() {
+                Self::unreserve_cards(&auction.seller, auction.card_id, auction.amount);
+            }
+            Self::deposit_event(Event::AuctionSettled(auction_id, winner));
+        }
+
+        /// Moves `amount` free copies of `card_id` from `from` to `to`.
+        fn do_transfer(from: &T::AccountId, to: &T::AccountId, card_id: CardId, amount: u16) -> DispatchResult {
+            Self::ensure_free_cards(from, card_id, amount)?;
+            if from == to {
+                return Ok(());
+            }
+            let received = Self::owners(to, card_id).unwrap_or(0).checked_add(amount)
+                .ok_or(Error::<T>::TooManyCopies)?;
+
+            <CardOwners<T>>::mutate_exists(from, card_id, |owned| {
+                let left = owned.unwrap_or(0).saturating_sub(amount);
+                *owned = if left == 0 { None } else { Some(left) };
+            });
+            <CardOwners<T>>::insert(to, card_id, received);
+            Ok(())
+        }
+    }
+
+    fn has_repeats<I: PartialEq>(items: &[I]) -> bool {
+        items.iter().enumerate().any(|(i, item)| items[..i].contains(item))
+    }
+}
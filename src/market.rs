@@ -0,0 +1,234 @@
+use frame_support::{
+    codec::{Decode, Encode},
+    dispatch::DispatchResult,
+    sp_runtime::{
+        traits::{AtLeast32BitUnsigned, Saturating},
+        Perbill, RuntimeDebug,
+    },
+};
+use sp_std::vec::Vec;
+use crate::card::CardId;
+
+pub type OrderId = u32;
+
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum Side {
+    /// Offer to sell, the copies are reserved in the seller's collection
+    Ask,
+    /// Offer to buy, the price is reserved from the buyer's balance
+    Bid,
+}
+
+impl Side {
+    pub fn opposite(self) -> Self {
+        match self {
+            Side::Ask => Side::Bid,
+            Side::Bid => Side::Ask,
+        }
+    }
+}
+
+/// Limit order in the order book of a card.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct Order<AccountId, Balance, BlockNumber> {
+    pub owner: AccountId,
+    pub card_id: CardId,
+    pub side: Side,
+    /// Price of a single copy
+    pub price: Balance,
+    /// Copies still to trade
+    pub amount: u16,
+    /// Block in which the order is cancelled if it is not filled
+    pub expires: Option<BlockNumber>,
+}
+
+impl<AccountId, Balance: PartialOrd, BlockNumber> Order<AccountId, Balance, BlockNumber> {
+    /// Whether this order trades with a resting order at `price` on the opposite side.
+    pub fn crosses(&self, price: &Balance) -> bool {
+        match self.side {
+            Side::Ask => price >= &self.price,
+            Side::Bid => price <= &self.price,
+        }
+    }
+}
+
+impl<AccountId, Balance, BlockNumber: PartialOrd> Order<AccountId, Balance, BlockNumber> {
+    /// Whether the order has expired by block `now`, even if its removal is carried over.
+    pub fn is_expired(&self, now: &BlockNumber) -> bool {
+        self.expires.as_ref().map_or(false, |expires| now >= expires)
+    }
+}
+
+pub type AuctionId = u32;
+
+/// Timed ascending auction for several copies of a card, sold as one lot.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct Auction<AccountId, Balance, BlockNumber> {
+    pub seller: AccountId,
+    pub card_id: CardId,
+    /// Copies in the lot, these are reserved in the seller's collection
+    pub amount: u16,
+    /// Lowest acceptable first bid
+    pub reserve_price: Balance,
+    /// Every following bid must beat the best one by at least this much
+    pub min_increment: Balance,
+    /// Block in which the auction is settled
+    pub end: BlockNumber,
+    /// Best bidder and the bid reserved from their balance
+    pub best_bid: Option<(AccountId, Balance)>,
+}
+
+/// Copies of a card offered at a price falling linearly from `start_price` to `end_price`
+/// between blocks `start` and `end`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct DutchOffer<Balance, BlockNumber> {
+    pub start_price: Balance,
+    pub end_price: Balance,
+    pub start: BlockNumber,
+    pub end: BlockNumber,
+    /// Copies still offered, these are reserved in the seller's collection
+    pub amount: u16,
+}
+
+impl<Balance, BlockNumber> DutchOffer<Balance, BlockNumber>
+where
+    Balance: AtLeast32BitUnsigned + Copy,
+    BlockNumber: AtLeast32BitUnsigned + Copy,
+{
+    /// Price of a single copy at block `now`, the price stays at `end_price` after `end`.
+    pub fn price_at(&self, now: BlockNumber) -> Balance {
+        if now <= self.start {
+            return self.start_price;
+        }
+        if now >= self.end {
+            return self.end_price;
+        }
+        let elapsed = Perbill::from_rational_approximation(now - self.start, self.end - self.start);
+        self.start_price.saturating_sub(elapsed * self.start_price.saturating_sub(self.end_price))
+    }
+}
+
+/// Commit-reveal auction for several copies of a card, the best bidder pays the second best bid.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct SealedAuction<AccountId, Balance, BlockNumber> {
+    pub seller: AccountId,
+    pub card_id: CardId,
+    /// Copies in the lot, these are reserved in the seller's collection
+    pub amount: u16,
+    /// Lowest bid that can win, also the price if only one bid reaches it
+    pub reserve_price: Balance,
+    /// First block in which bids are revealed instead of committed
+    pub commit_end: BlockNumber,
+    /// Block in which the auction is settled
+    pub reveal_end: BlockNumber,
+    /// Number of committed bids
+    pub bids: u32,
+    /// Best revealed bidder and the bid reserved from their balance
+    pub best_bid: Option<(AccountId, Balance)>,
+    /// Second best revealed bid
+    pub second_bid: Option<Balance>,
+}
+
+/// Bid hidden behind a hash of `(bidder, value, salt)` until it is revealed.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct SealedBid<Hash, Balance> {
+    pub commitment: Hash,
+    /// Reserved on commit, slashed if the bid is not revealed
+    pub deposit: Balance,
+    pub revealed: Option<Balance>,
+}
+
+pub type TradeId = u32;
+
+/// Offer to swap cards, optionally sweetened with balance, with one counterparty.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct TradeOffer<AccountId, Balance, BlockNumber> {
+    pub proposer: AccountId,
+    pub counterparty: AccountId,
+    /// Copies the proposer gives, these are reserved in the proposer's collection
+    pub give: Vec<(CardId, u16)>,
+    /// Copies the counterparty gives in return
+    pub take: Vec<(CardId, u16)>,
+    /// Balance the proposer adds, reserved from the proposer's balance
+    pub give_balance: Balance,
+    /// Balance the counterparty adds
+    pub take_balance: Balance,
+    /// Reserved from the proposer until the offer is closed
+    pub deposit: Balance,
+    /// Block in which the offer is cancelled if it is not accepted
+    pub expires: Option<BlockNumber>,
+}
+
+impl<AccountId, Balance, BlockNumber: PartialOrd> TradeOffer<AccountId, Balance, BlockNumber> {
+    /// Whether the offer has expired by block `now`, even if its removal is carried over.
+    pub fn is_expired(&self, now: &BlockNumber) -> bool {
+        self.expires.as_ref().map_or(false, |expires| now >= expires)
+    }
+}
+
+/// Price statistics of a card, prices are per copy.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct MarketStats<Balance, BlockNumber> {
+    pub last_price: Balance,
+    /// Block of the last sale
+    pub last_sale: BlockNumber,
+    /// Highest price ever paid
+    pub high: Balance,
+    /// Lowest price ever paid
+    pub low: Balance,
+    /// Balance paid in all sales
+    pub volume: Balance,
+    /// Copies sold in all sales
+    pub copies_sold: u64,
+}
+
+/// Sale kept in the recent trades of a card.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct SaleRecord<AccountId, Balance, BlockNumber> {
+    pub seller: AccountId,
+    pub buyer: AccountId,
+    pub amount: u16,
+    /// Price of a single copy
+    pub price: Balance,
+    pub block: BlockNumber,
+}
+
+/// Offer to sell copies of a card priced in an asset other than the native currency.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct AssetListing<AssetId, Balance> {
+    pub asset_id: AssetId,
+    /// Price of a single copy
+    pub price: Balance,
+    pub amount: u16,
+}
+
+/// Assets other than the native currency cards can be priced in, e.g. those of `pallet_assets`.
+pub trait AssetTransfer<AccountId, AssetId, Balance> {
+    /// Balance of `asset_id` `who` can transfer.
+    fn transferable_balance(asset_id: AssetId, who: &AccountId) -> Balance;
+
+    /// Moves `amount` of `asset_id` from `from` to `to`.
+    fn transfer(asset_id: AssetId, from: &AccountId, to: &AccountId, amount: Balance) -> DispatchResult;
+}
+
+pub type RentalId = u32;
+
+/// Offer to rent out copies of a card, the copies are reserved in the owner's collection.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct RentalOffer<Balance, BlockNumber> {
+    /// Price of renting a single copy for the whole term
+    pub price: Balance,
+    /// Blocks the copies are rented for
+    pub duration: BlockNumber,
+    pub amount: u16,
+}
+
+/// Copies lent to a renter until block `end`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct Rental<AccountId, BlockNumber> {
+    pub owner: AccountId,
+    pub renter: AccountId,
+    pub card_id: CardId,
+    pub amount: u16,
+    pub end: BlockNumber,
+}
@@ -0,0 +1,200 @@
+use frame_support::{
+    codec::{Decode, Encode},
+    sp_runtime::RuntimeDebug,
+    storage::StoragePrefixedMap,
+    traits::Get,
+    weights::Weight,
+};
+use sp_std::vec::Vec;
+use crate::card::{Card, CardInfo, CardType, Color, Keywords, ManaCost, PowerToughness, Rarity, TypeLine, H256};
+use crate::pallet::{CardInfos, CardRegistry, CardRoyalties, Config, StorageVersion, VolumeHistory};
+
+/// Layouts of the pallet storage.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Releases {
+    /// Cards have a single `CardType`.
+    V0,
+    /// Cards have a `TypeLine`.
+    V1,
+    /// Cards have a `ManaCost`.
+    V2,
+    /// Cards have keywords, power and toughness and loyalty.
+    V3,
+    /// Cards have a `Rarity`.
+    V4,
+    /// Creators are kept in `CardInfos` only, `CardRoyalties` holds just the rate.
+    V5,
+    /// `VolumeHistory` is kept in buckets of blocks.
+    V6,
+}
+
+impl Releases {
+    pub const LATEST: Releases = Releases::V6;
+}
+
+impl Default for Releases {
+    fn default() -> Self {Releases::V0}
+}
+
+/// Card as stored before `Releases::V1`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq)]
+pub struct CardV0 {
+    pub name: Vec<u8>,
+    pub card_type: CardType,
+    /// Any `u8` was accepted as a color
+    pub color: u8,
+    pub rules: Vec<u8>,
+    pub image: H256,
+}
+
+/// Card as stored before `Releases::V2`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq)]
+pub struct CardV1 {
+    pub name: Vec<u8>,
+    pub type_line: TypeLine,
+    pub color: Color,
+    pub rules: Vec<u8>,
+    pub image: H256,
+}
+
+/// Card as stored before `Releases::V3`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq)]
+pub struct CardV2 {
+    pub name: Vec<u8>,
+    pub mana_cost: ManaCost,
+    pub type_line: TypeLine,
+    pub color: Color,
+    pub rules: Vec<u8>,
+    pub image: H256,
+}
+
+/// Card as stored before `Releases::V4`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq)]
+pub struct CardV3 {
+    pub name: Vec<u8>,
+    pub mana_cost: ManaCost,
+    pub type_line: TypeLine,
+    pub color: Color,
+    pub rules: Vec<u8>,
+    pub keywords: Keywords,
+    pub power_toughness: Option<PowerToughness>,
+    pub loyalty: Option<u8>,
+    pub image: H256,
+}
+
+/// Royalty as stored before `Releases::V5`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq)]
+pub struct RoyaltyV4<AccountId> {
+    pub creator: AccountId,
+    pub per_mill: u16,
+}
+
+/// Upgrades the storage to `Releases::LATEST`.
+pub fn migrate<T: Config>() -> Weight {
+    let version = <StorageVersion<T>>::get();
+    if version == Releases::LATEST {
+        return T::DbWeight::get().reads(1);
+    }
+    let mut translated: Weight = 0;
+    match version {
+        Releases::V0 => <CardRegistry<T>>::translate::<CardV0, _>(|_, card| {
+            translated += 1;
+            Some(v3_to_v4(v2_to_v3(v1_to_v2(v0_to_v1::<T>(card)))))
+        }),
+        Releases::V1 => <CardRegistry<T>>::translate::<CardV1, _>(|_, card| {
+            translated += 1;
+            Some(v3_to_v4(v2_to_v3(v1_to_v2(card))))
+        }),
+        Releases::V2 => <CardRegistry<T>>::translate::<CardV2, _>(|_, card| {
+            translated += 1;
+            Some(v3_to_v4(v2_to_v3(card)))
+        }),
+        Releases::V3 => <CardRegistry<T>>::translate::<CardV3, _>(|_, card| {
+            translated += 1;
+            Some(v3_to_v4(card))
+        }),
+        Releases::V4 | Releases::V5 | Releases::V6 => (),
+    }
+    if version < Releases::V5 {
+        // Cards created before their provenance was recorded get a creator without a date.
+        <CardRoyalties<T>>::translate::<RoyaltyV4<T::AccountId>, _>(|card_id, royalty| {
+            translated += 2;
+            if !<CardInfos<T>>::contains_key(card_id) {
+                <CardInfos<T>>::insert(card_id, CardInfo {
+                    creator: royalty.creator,
+                    created: Default::default(),
+                    timestamp: Default::default(),
+                    initial_supply: 0,
+                });
+            }
+            Some(royalty.per_mill)
+        });
+    }
+    if version < Releases::V6 {
+        // The volume per block does not fit the buckets, the rolling volume starts over.
+        <VolumeHistory<T> as StoragePrefixedMap<(T::BlockNumber, T::Balance)>>::remove_all();
+        translated += 1;
+    }
+    <StorageVersion<T>>::put(Releases::LATEST);
+    T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+}
+
+/// Replaces the card type by a type line with that single type and drops undefined color bits.
+fn v0_to_v1<T: Config>(card: CardV0) -> CardV1 {
+    CardV1 {
+        name: bound::<T::MaxNameLength>(card.name),
+        type_line: TypeLine { supertypes: Vec::new(), types: sp_std::vec![card.card_type], subtypes: Vec::new() },
+        color: Color::from_bits_truncate(card.color),
+        rules: bound::<T::MaxRulesLength>(card.rules),
+        image: card.image,
+    }
+}
+
+/// Adds an empty mana cost.
+fn v1_to_v2(card: CardV1) -> CardV2 {
+    CardV2 {
+        name: card.name,
+        mana_cost: ManaCost::default(),
+        type_line: card.type_line,
+        color: card.color,
+        rules: card.rules,
+        image: card.image,
+    }
+}
+
+/// Adds no keywords and no stats.
+fn v2_to_v3(card: CardV2) -> CardV3 {
+    CardV3 {
+        name: card.name,
+        mana_cost: card.mana_cost,
+        type_line: card.type_line,
+        color: card.color,
+        rules: card.rules,
+        keywords: Keywords::default(),
+        power_toughness: None,
+        loyalty: None,
+        image: card.image,
+    }
+}
+
+/// Makes every card common.
+fn v3_to_v4(card: CardV3) -> Card {
+    Card {
+        name: card.name,
+        mana_cost: card.mana_cost,
+        type_line: card.type_line,
+        rarity: Rarity::Common,
+        color: card.color,
+        rules: card.rules,
+        keywords: card.keywords,
+        power_toughness: card.power_toughness,
+        loyalty: card.loyalty,
+        image: card.image,
+    }
+}
+
+/// Text stored before it was bounded, cut to the bound.
+fn bound<S: Get<u32>>(mut text: Vec<u8>) -> Vec<u8> {
+    text.truncate(S::get() as usize);
+    text
+}
@@ -1,11 +1,12 @@
 use super::*;
 
 use sp_core::H256;
-use frame_support::parameter_types;
+use frame_support::{assert_ok, dispatch::DispatchResult, parameter_types, traits::OnInitialize};
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
 };
 use frame_system as system;
+use crate::market::AssetTransfer;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -18,7 +19,9 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
-		Balances: pallet_balances::{Module, Call, Storage, Event<T>},
+		Timestamp: pallet_timestamp::{Module, Call, Storage, Inherent},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Module, Call, Storage, Event<T>},
 		Cards: pallet::{Module, Call, Storage, Event<T>},
 	}
 );
@@ -27,6 +30,40 @@ parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 	pub const SS58Prefix: u8 = 42;
 	pub const ExistentialDeposit: u64 = 1;
+	pub const MinimumPeriod: u64 = 5;
+	pub const MinAuctionDuration: u64 = 2;
+	pub const MaxAuctionDuration: u64 = 100;
+	pub const SealedBidDeposit: u64 = 10;
+	pub const MaxSealedBids: u32 = 3;
+	pub const MaxFillsPerCall: u32 = 3;
+	pub const MaxOrdersPerBook: u32 = 5;
+	pub const MaxTradeItems: u32 = 3;
+	pub const TradeDeposit: u64 = 5;
+	pub const MaxRoyalty: u16 = 200;
+	pub const DefaultMarketFee: u16 = 0;
+	pub const FeeDestination: AccountId = TREASURY;
+	pub const MaxExpiriesPerBlock: u32 = 2;
+	pub const VolumeWindow: u64 = 10;
+	pub const VolumeBuckets: u32 = 5;
+	pub const MaxRecentTrades: u32 = 3;
+	pub const MaxRentalDuration: u64 = 20;
+	pub const MaxNameLength: u32 = 16;
+	pub const MaxRulesLength: u32 = 64;
+	pub const MaxSubtypes: u32 = 3;
+	pub const MaxManaPips: u32 = 4;
+	pub const MaxCommonPack: u16 = 100;
+	pub const MaxUncommonPack: u16 = 50;
+	pub const MaxRarePack: u16 = 10;
+	pub const MaxMythicPack: u16 = 3;
+	pub const MaxPromoPack: u16 = 1;
+	pub const MaxSetCodeLength: u32 = 4;
+	pub const MaxSetSize: u32 = 5;
+	pub const MaxErrata: u32 = 2;
+	pub const AssetDepositBase: u64 = 1;
+	pub const AssetDepositPerZombie: u64 = 1;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u64 = 1;
+	pub const MetadataDepositPerByte: u64 = 1;
 }
 
 impl system::Config for Test {
@@ -56,6 +93,37 @@ impl system::Config for Test {
 
 impl pallet::Config for Test {
 	type Event = Event;
+	type Currency = Balances;
+	type AssetId = u32;
+	type Assets = AssetsAdapter;
+	type MinAuctionDuration = MinAuctionDuration;
+	type MaxAuctionDuration = MaxAuctionDuration;
+	type SealedBidDeposit = SealedBidDeposit;
+	type MaxSealedBids = MaxSealedBids;
+	type MaxFillsPerCall = MaxFillsPerCall;
+	type MaxOrdersPerBook = MaxOrdersPerBook;
+	type MaxTradeItems = MaxTradeItems;
+	type TradeDeposit = TradeDeposit;
+	type MaxNameLength = MaxNameLength;
+	type MaxRulesLength = MaxRulesLength;
+	type MaxSubtypes = MaxSubtypes;
+	type MaxManaPips = MaxManaPips;
+	type MaxCommonPack = MaxCommonPack;
+	type MaxUncommonPack = MaxUncommonPack;
+	type MaxRarePack = MaxRarePack;
+	type MaxMythicPack = MaxMythicPack;
+	type MaxPromoPack = MaxPromoPack;
+	type MaxSetCodeLength = MaxSetCodeLength;
+	type MaxSetSize = MaxSetSize;
+	type MaxErrata = MaxErrata;
+	type MaxRoyalty = MaxRoyalty;
+	type DefaultMarketFee = DefaultMarketFee;
+	type FeeDestination = FeeDestination;
+	type MaxExpiriesPerBlock = MaxExpiriesPerBlock;
+	type VolumeWindow = VolumeWindow;
+	type VolumeBuckets = VolumeBuckets;
+	type MaxRecentTrades = MaxRecentTrades;
+	type MaxRentalDuration = MaxRentalDuration;
 }
 
 impl pallet_balances::Config for Test {
@@ -70,10 +138,57 @@ impl pallet_balances::Config for Test {
 	// type ReserveIdentifier = ();
 }
 
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDepositBase = AssetDepositBase;
+	type AssetDepositPerZombie = AssetDepositPerZombie;
+	type StringLimit = StringLimit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type WeightInfo = ();
+}
+
+/// Prices cards in the assets of `pallet_assets`.
+pub struct AssetsAdapter;
+
+impl AssetTransfer<AccountId, u32, u64> for AssetsAdapter {
+	fn transferable_balance(asset_id: u32, who: &AccountId) -> u64 {
+		Assets::balance(asset_id, *who)
+	}
+
+	fn transfer(asset_id: u32, from: &AccountId, to: &AccountId, amount: u64) -> DispatchResult {
+		Assets::transfer(Origin::signed(*from), asset_id, *to, amount)
+	}
+}
+
 pub type AccountId = u64;
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
 pub const MIRA: AccountId = 3;
+pub const DAVE: AccountId = 4;
+pub const TREASURY: AccountId = 5;
+
+pub const INITIAL_BALANCE: u64 = 1_000;
+/// Asset created by `create_gems`.
+pub const GEM: u32 = 7;
+
+/// Creates the `GEM` asset and gives `INITIAL_BALANCE` of it to BOB and MIRA.
+pub fn create_gems() {
+	assert_ok!(Assets::force_create(Origin::root(), GEM, TREASURY, 10, 1));
+	assert_ok!(Assets::mint(Origin::signed(TREASURY), GEM, BOB, INITIAL_BALANCE));
+	assert_ok!(Assets::mint(Origin::signed(TREASURY), GEM, MIRA, INITIAL_BALANCE));
+}
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
@@ -85,9 +200,21 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	};
 
 	config.assimilate_storage(&mut storage).unwrap();
+
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(ALICE, INITIAL_BALANCE), (BOB, INITIAL_BALANCE), (MIRA, INITIAL_BALANCE), (DAVE, INITIAL_BALANCE), (TREASURY, INITIAL_BALANCE)],
+	}.assimilate_storage(&mut storage).unwrap();
 	
 	let mut t: sp_io::TestExternalities = storage.into();
 
 	t.execute_with(|| System::set_block_number(1) );
 	t
-}
\ No newline at end of file
+}
+
+/// Runs the pallet hooks of every block up to and including `n`.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		Cards::on_initialize(System::block_number());
+	}
+}
@@ -1,17 +1,42 @@
 use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use frame_support::{assert_ok, assert_noop, traits::Get};
+use sp_runtime::traits::Hash;
 
 use crate::card::*;
+use crate::market::{DutchOffer, MarketStats, Order, OrderId, SaleRecord, Side};
 
 #[test]
 fn creator_creates_card_ok() {
 	new_test_ext().execute_with(|| {
 		let card = new_card();
 		// signed account create
-		assert_ok!(Cards::create_card_pack(Origin::signed(ALICE), card.clone(), 10));
+		assert_ok!(Cards::create_card_pack(Origin::signed(ALICE), card.clone(), 10, 0, None));
 		// read from storage
 		let id = Cards::previous_card_id();
-		assert_eq!(Cards::cards(id), Some(card));
+		let stored = Cards::cards(id).unwrap();
+		assert_eq!(stored.name, card.name);
+		assert_eq!(stored.rules, card.rules);
+		assert_eq!(stored.type_line.types, card.type_line.types);
+		assert_eq!(stored.type_line.subtypes, card.type_line.subtypes);
+		assert_eq!((stored.color, stored.image), (card.color, card.image));
+	});
+}
+
+#[test]
+fn create_card_rejects_bad_text(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let long_name = Card { name: vec![b'a'; MaxNameLength::get() as usize + 1], ..new_card() };
+		assert_noop!(Cards::create_card_pack(creator.clone(), long_name, 10, 0, None), Error::<Test>::NameTooLong);
+		let long_rules = Card { rules: vec![b'a'; MaxRulesLength::get() as usize + 1], ..new_card() };
+		assert_noop!(Cards::create_card_pack(creator.clone(), long_rules, 10, 0, None), Error::<Test>::RulesTooLong);
+		let not_utf8 = Card { name: vec![0xff, 0xfe], ..new_card() };
+		assert_noop!(Cards::create_card_pack(creator.clone(), not_utf8, 10, 0, None), Error::<Test>::InvalidEncoding);
+		let control = Card { rules: b"Haste\nTrample".to_vec(), ..new_card() };
+		assert_noop!(Cards::create_card_pack(creator.clone(), control, 10, 0, None), Error::<Test>::InvalidEncoding);
+
+		let longest = Card { name: vec![b'a'; MaxNameLength::get() as usize], ..new_card() };
+		assert_ok!(Cards::create_card_pack(creator, longest, 10, 0, None));
 	});
 }
 
@@ -19,16 +44,25 @@ fn creator_creates_card_ok() {
 fn create_card_pack(creator: Origin) -> u32 {
 	let card = new_card();
 	// signed account create
-	assert_ok!(Cards::create_card_pack(creator, card.clone(), 10));
+	assert_ok!(Cards::create_card_pack(creator, card.clone(), 10, 0, None));
 	Cards::previous_card_id()
 }
 
 fn new_card() -> Card {
 	Card { 
-		name: vec!(1,4,45,3,2),
-		card_type: CardType::Creature,
-		color: RED&BLACK,
-		rules: vec!(1,4,45,3,2),
+		name: b"Goblin Guide".to_vec(),
+		mana_cost: ManaCost { x: 0, generic: 1, pips: vec![ManaPip::Colored(RED), ManaPip::Colored(BLACK)] },
+		type_line: TypeLine {
+			supertypes: vec![],
+			types: vec![CardType::Creature],
+			subtypes: vec![b"Goblin".to_vec(), b"Warrior".to_vec()],
+		},
+		rarity: Rarity::Common,
+		color: RED | BLACK,
+		rules: b"Haste".to_vec(),
+		keywords: Keywords::default().with(Keyword::Haste),
+		power_toughness: Some(PowerToughness { power: 2, toughness: 2 }),
+		loyalty: None,
 		image: H256([56u8; 32]),
 	 }
 }
@@ -38,7 +72,7 @@ fn only_creator_can_create_card_error() {
 	new_test_ext().execute_with(|| {
 		let id = Cards::previous_card_id();
 		let card = new_card();
-		assert_noop!(Cards::create_card_pack(Origin::signed(BOB), card.clone(), 10), 
+		assert_noop!(Cards::create_card_pack(Origin::signed(BOB), card.clone(), 10, 0, None), 
 			Error::<Test>::NoPermission);
 		assert_eq!(id, Cards::previous_card_id());
 	})
@@ -57,7 +91,7 @@ fn creator_assigns_new_creator_ok() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(Cards::set_creator(Origin::signed(ALICE), BOB));
 		assert!(crate::is_creator::<Test>(BOB));
-		assert_ok!(Cards::create_card_pack(Origin::signed(BOB), new_card(), 10));
+		assert_ok!(Cards::create_card_pack(Origin::signed(BOB), new_card(), 10, 0, None));
 	})
 }
 
@@ -76,7 +110,7 @@ fn creator_withdraws_creator_ok() {
 		assert!(crate::is_creator::<Test>(BOB));
 		assert_ok!(Cards::withdraw_creator(Origin::signed(ALICE), BOB));
 		assert!(crate::is_creator::<Test>(BOB) != true);
-		assert_noop!(Cards::create_card_pack(Origin::signed(BOB), new_card(), 10), 
+		assert_noop!(Cards::create_card_pack(Origin::signed(BOB), new_card(), 10, 0, None), 
 			Error::<Test>::NoPermission);	})
 }
 
@@ -88,7 +122,7 @@ fn only_creator_can_withdraw_crearor_error() {
 		assert_noop!(Cards::withdraw_creator(Origin::signed(MIRA), BOB), 
 			Error::<Test>::NoPermission);
 		assert!(crate::is_creator::<Test>(BOB));
-		assert_ok!(Cards::create_card_pack(Origin::signed(BOB), new_card(), 10));	
+		assert_ok!(Cards::create_card_pack(Origin::signed(BOB), new_card(), 10, 0, None));	
 		})
 }
 
@@ -121,6 +155,19 @@ fn transfer_card_ok() {
 	})
 }
 
+#[test]
+fn transfer_card_too_many_copies_error() {
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		crate::CardOwners::<Test>::insert(BOB, card_id, u16::MAX);
+
+		assert_noop!(Cards::transfer(creator, card_id, BOB), Error::<Test>::TooManyCopies);
+		assert_eq!(Cards::owners(ALICE, card_id), Some(10));
+		assert_eq!(Cards::owners(BOB, card_id), Some(u16::MAX));
+	})
+}
+
 #[test]
 fn transfer_card_not_owned_error() {
 	new_test_ext().execute_with(|| {
@@ -135,17 +182,29 @@ fn transfer_card_not_owned_error() {
 	})
 }
 
+fn last_order_id() -> OrderId {
+	Cards::next_order_id() - 1
+}
+
+fn ask(owner: AccountId, card_id: CardId, price: u64, amount: u16) -> Order<AccountId, u64, u64> {
+	Order { owner, card_id, side: Side::Ask, price, amount, expires: None }
+}
+
 #[test]
 fn set_card_price_ok(){
 	new_test_ext().execute_with(|| {
 		let creator = Origin::signed(ALICE);
 		let card_id = create_card_pack(creator.clone());
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(100));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		let first = last_order_id();
+		assert_eq!(Cards::orders(first), Some(ask(ALICE, card_id, 100, 1)));
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator, card_id, 55));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(55));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator, card_id, 55, 2, None));
+		let second = last_order_id();
+		assert_eq!(Cards::orders(second), Some(ask(ALICE, card_id, 55, 2)));
+		assert_eq!(Cards::asks(card_id), vec![(55, second), (100, first)]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 3);
 	})
 }
 
@@ -156,22 +215,47 @@ fn set_card_price_error(){
 		let creator2 = Origin::signed(BOB);
 		let card_id = create_card_pack(creator.clone());
 
-		assert_noop!(Cards::set_card_for_sale_with_price(creator2, card_id, 100), Error::<Test>::CardNotOwned) ;
+		assert_noop!(Cards::set_card_for_sale_with_price(creator2, card_id, 100, 1, None), Error::<Test>::CardNotOwned) ;
+		assert_noop!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 0, None), Error::<Test>::ZeroAmount) ;
+		assert_noop!(Cards::set_card_for_sale_with_price(creator, card_id, 100, 11, None), Error::<Test>::NotEnoughCards) ;
 	})
 }
 
-
 #[test]
 fn remove_card_price_ok(){
 	new_test_ext().execute_with(|| {
 		let creator = Origin::signed(ALICE);
 		let card_id = create_card_pack(creator.clone());
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(100));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		let order_id = last_order_id();
+		assert_eq!(Cards::orders(order_id), Some(ask(ALICE, card_id, 100, 1)));
+
+		assert_ok!(Cards::cancel_order(creator.clone(), order_id));
+		assert_eq!(Cards::orders(order_id), None);
+		assert_eq!(Cards::asks(card_id), vec![]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+	})
+}
+
+#[test]
+fn remove_card_from_sale_cancels_own_sell_orders(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		assert_ok!(Cards::transfer(creator.clone(), card_id, BOB));
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		assert_eq!(System::events().last().map(|record| record.event.clone()),
+			Some(Event::from(crate::Event::CardSetForSale(card_id, ALICE, 100, 1))));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 90, 2, None));
+		assert_ok!(Cards::set_card_for_sale_with_price(Origin::signed(BOB), card_id, 95, 1, None));
+		let bob_order = last_order_id();
 
 		assert_ok!(Cards::remove_card_from_sale(creator.clone(), card_id));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_eq!(Cards::asks(card_id), vec![(95, bob_order)]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_noop!(Cards::remove_card_from_sale(creator, card_id), Error::<Test>::CardNotForSale);
 	})
 }
 
@@ -182,17 +266,14 @@ fn remove_card_price_error(){
 		let creator2 = Origin::signed(BOB);
 		let card_id = create_card_pack(creator.clone());
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(100));
-
-		assert_ok!(Cards::remove_card_from_sale(creator.clone(), card_id));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		let order_id = last_order_id();
 
-		assert_noop!(Cards::remove_card_from_sale(creator.clone(), card_id), Error::<Test>::CardNotForSale);
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_noop!(Cards::cancel_order(creator2.clone(), order_id), Error::<Test>::NoPermission);
+		assert_eq!(Cards::orders(order_id), Some(ask(ALICE, card_id, 100, 1)));
 
-		assert_noop!(Cards::remove_card_from_sale(creator2.clone(), card_id), Error::<Test>::CardNotForSale);
-		assert_eq!(Cards::cards_for_sale(card_id, BOB), None);
+		assert_ok!(Cards::cancel_order(creator.clone(), order_id));
+		assert_noop!(Cards::cancel_order(creator.clone(), order_id), Error::<Test>::OrderNotFound);
 	})
 }
 
@@ -202,19 +283,19 @@ fn create_and_buy_one_card_ok(){
 		let creator = Origin::signed(ALICE);
 		let buyer = Origin::signed(BOB);
 		let card = new_card();
-		assert_ok!(Cards::create_card_pack(creator.clone(), card.clone(), 1));
+		assert_ok!(Cards::create_card_pack(creator.clone(), card.clone(), 1, 0, None));
 		let card_id = Cards::previous_card_id();
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(100));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		let order_id = last_order_id();
 
-		assert_ok!(Cards::buy(buyer.clone(), card_id, ALICE));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_ok!(Cards::buy(buyer.clone(), order_id, 1, 1_000));
+		assert_eq!(Cards::orders(order_id), None);
+		assert_eq!(Cards::asks(card_id), vec![]);
 		assert_eq!(Cards::owners(ALICE, card_id), None);
 		assert_eq!(Cards::owners(BOB, card_id), Some(1));
  
-		assert_noop!(Cards::buy(buyer.clone(), card_id, ALICE), Error::<Test>::CardNotOwned);
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_noop!(Cards::buy(buyer.clone(), order_id, 1, 1_000), Error::<Test>::OrderNotFound);
 		assert_eq!(Cards::owners(ALICE, card_id), None);
 		assert_eq!(Cards::owners(BOB, card_id), Some(1));
 	})
@@ -227,16 +308,17 @@ fn buy_card_ok(){
 		let buyer = Origin::signed(BOB);
 		let card_id = create_card_pack(creator.clone());
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(100));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		let order_id = last_order_id();
 
-		assert_ok!(Cards::buy(buyer.clone(), card_id, ALICE));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_ok!(Cards::buy(buyer.clone(), order_id, 1, 1_000));
+		assert_eq!(Cards::orders(order_id), None);
 		assert_eq!(Cards::owners(ALICE, card_id), Some(9));
 		assert_eq!(Cards::owners(BOB, card_id), Some(1));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 100);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 100);
 
-		assert_noop!(Cards::buy(buyer.clone(), card_id, ALICE), Error::<Test>::CardNotForSale);
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_noop!(Cards::buy(buyer.clone(), order_id, 1, 1_000), Error::<Test>::OrderNotFound);
 		assert_eq!(Cards::owners(ALICE, card_id), Some(9));
 		assert_eq!(Cards::owners(BOB, card_id), Some(1));
 	})
@@ -250,9 +332,12 @@ fn buy_card_error(){
 		let buyer = Origin::signed(100);
 		let card_id = create_card_pack(creator.clone());
 
-		assert_noop!(Cards::buy(buyer.clone(), card_id, ALICE), Error::<Test>::CardNotForSale);
+		assert_noop!(Cards::buy(buyer.clone(), 0, 1, 1_000), Error::<Test>::OrderNotFound);
 		assert_eq!(Cards::owners(ALICE, card_id), Some(10));
 		assert_eq!(Cards::owners(BOB, card_id), None);
+
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 10, 1, None));
+		assert_noop!(Cards::buy(buyer.clone(), last_order_id(), 1, 1_000), Error::<Test>::WrongOrderSide);
 	})
 }
 
@@ -264,43 +349,1542 @@ fn buy_card_after_transfer_ok(){
 		let buyer2 = Origin::signed(MIRA);
 		let card_id = create_card_pack(creator.clone());
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 199));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(199));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 199, 1, None));
+		let alice_order = last_order_id();
 
 		assert_ok!(Cards::transfer(creator, card_id, BOB));
-		assert_ok!(Cards::set_card_for_sale_with_price(buyer.clone(), card_id, 100));
-		assert_eq!(Cards::cards_for_sale(card_id, BOB), Some(100));
+		assert_ok!(Cards::set_card_for_sale_with_price(buyer.clone(), card_id, 100, 1, None));
+		let bob_order = last_order_id();
+		assert_eq!(Cards::asks(card_id), vec![(100, bob_order), (199, alice_order)]);
 
-		assert_ok!(Cards::buy(buyer2.clone(), card_id, BOB));
-		assert_eq!(Cards::cards_for_sale(card_id, BOB), None);
+		assert_ok!(Cards::buy(buyer2.clone(), bob_order, 1, 1_000));
+		assert_eq!(Cards::orders(bob_order), None);
 
-		assert_ok!(Cards::buy(buyer2.clone(), card_id, ALICE));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), None);
+		assert_ok!(Cards::buy(buyer2.clone(), alice_order, 1, 1_000));
+		assert_eq!(Cards::orders(alice_order), None);
 
 		assert_eq!(Cards::owners(ALICE, card_id), Some(8));
 		assert_eq!(Cards::owners(BOB, card_id), None);
 		assert_eq!(Cards::owners(MIRA, card_id), Some(2));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 199);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE + 100);
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE - 299);
 	})
 }
 
 
 #[test]
 fn buy_card_after_transfer_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card = new_card();
+		assert_ok!(Cards::create_card_pack(creator.clone(), card.clone(), 1, 0, None));
+		let card_id = Cards::previous_card_id();
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		let order_id = last_order_id();
+
+		// listed copy is reserved
+		assert_noop!(Cards::transfer(creator.clone(), card_id, BOB), Error::<Test>::CardsReserved);
+
+		assert_ok!(Cards::cancel_order(creator.clone(), order_id));
+		assert_ok!(Cards::transfer(creator, card_id, BOB));
+		assert_eq!(Cards::owners(BOB, card_id), Some(1));
+	})
+}
+
+#[test]
+fn buy_card_insufficient_balance_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let buyer = Origin::signed(BOB);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, INITIAL_BALANCE, 1, None));
+		let order_id = last_order_id();
+
+		// buyer must stay above the existential deposit
+		assert_noop!(Cards::buy(buyer.clone(), order_id, 1, INITIAL_BALANCE), Error::<Test>::InsufficientBalance);
+		assert_eq!(Cards::orders(order_id), Some(ask(ALICE, card_id, INITIAL_BALANCE, 1)));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(10));
+		assert_eq!(Cards::owners(BOB, card_id), None);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE);
+	})
+}
+
+#[test]
+fn buy_own_card_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		let order_id = last_order_id();
+
+		assert_noop!(Cards::buy(creator.clone(), order_id, 1, 1_000), Error::<Test>::CannotBuyOwnCard);
+		assert_eq!(Cards::orders(order_id), Some(ask(ALICE, card_id, 100, 1)));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(10));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE);
+	})
+}
+
+#[test]
+fn buy_part_of_offered_cards_ok(){
 	new_test_ext().execute_with(|| {
 		let creator = Origin::signed(ALICE);
 		let buyer = Origin::signed(BOB);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 4, None));
+		let order_id = last_order_id();
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 4);
+		assert_eq!(Cards::free_cards(&ALICE, card_id), 6);
+
+		assert_ok!(Cards::buy(buyer.clone(), order_id, 3, 1_000));
+		assert_eq!(Cards::orders(order_id), Some(ask(ALICE, card_id, 100, 1)));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 1);
+		assert_eq!(Cards::owners(ALICE, card_id), Some(7));
+		assert_eq!(Cards::owners(BOB, card_id), Some(3));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 300);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 300);
+
+		assert_noop!(Cards::buy(buyer.clone(), order_id, 2, 1_000), Error::<Test>::NotEnoughCardsForSale);
+		assert_noop!(Cards::buy(buyer.clone(), order_id, 0, 1_000), Error::<Test>::ZeroAmount);
+
+		assert_ok!(Cards::buy(buyer.clone(), order_id, 1, 1_000));
+		assert_eq!(Cards::orders(order_id), None);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_eq!(Cards::owners(ALICE, card_id), Some(6));
+		assert_eq!(Cards::owners(BOB, card_id), Some(4));
+	})
+}
+
+#[test]
+fn offered_cards_are_reserved(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
 		let card = new_card();
-		assert_ok!(Cards::create_card_pack(creator.clone(), card.clone(), 1));
+		assert_ok!(Cards::create_card_pack(creator.clone(), card.clone(), 3, 0, None));
+		let card_id = Cards::previous_card_id();
+
+		assert_noop!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 4, None), Error::<Test>::NotEnoughCards);
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 2, None));
+		let order_id = last_order_id();
+
+		assert_ok!(Cards::transfer(creator.clone(), card_id, BOB));
+		assert_noop!(Cards::transfer(creator.clone(), card_id, BOB), Error::<Test>::CardsReserved);
+		assert_noop!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 50, 1, None), Error::<Test>::CardsReserved);
+
+		assert_ok!(Cards::cancel_order(creator.clone(), order_id));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_ok!(Cards::transfer(creator.clone(), card_id, BOB));
+		assert_ok!(Cards::transfer(creator, card_id, BOB));
+		assert_eq!(Cards::owners(ALICE, card_id), None);
+		assert_eq!(Cards::owners(BOB, card_id), Some(3));
+	})
+}
+
+#[test]
+fn buy_card_price_above_maximum_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let buyer = Origin::signed(BOB);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 150, 2, None));
+		let order_id = last_order_id();
+
+		assert_noop!(Cards::buy(buyer.clone(), order_id, 2, 100), Error::<Test>::PriceAboveMaximum);
+		assert_eq!(Cards::owners(BOB, card_id), None);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE);
+
+		assert_ok!(Cards::buy(buyer.clone(), order_id, 2, 150));
+		assert_eq!(Cards::owners(BOB, card_id), Some(2));
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 300);
+	})
+}
+
+#[test]
+fn auction_settles_with_best_bid_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::start_auction(creator.clone(), card_id, 3, 100, 10, 5));
+		let auction_id = Cards::next_auction_id() - 1;
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 3);
+
+		assert_noop!(Cards::bid(Origin::signed(BOB), auction_id, 99), Error::<Test>::BidTooLow);
+		assert_ok!(Cards::bid(Origin::signed(BOB), auction_id, 100));
+		assert_eq!(Balances::reserved_balance(BOB), 100);
+
+		assert_noop!(Cards::bid(Origin::signed(MIRA), auction_id, 109), Error::<Test>::BidTooLow);
+		assert_ok!(Cards::bid(Origin::signed(MIRA), auction_id, 110));
+		// outbid bidder is refunded right away
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE);
+		assert_eq!(Balances::reserved_balance(MIRA), 110);
+
+		assert_noop!(Cards::cancel_auction(creator.clone(), auction_id), Error::<Test>::AuctionHasBids);
+
+		run_to_block(6);
+		assert_eq!(Cards::auctions(auction_id), None);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_eq!(Cards::owners(ALICE, card_id), Some(7));
+		assert_eq!(Cards::owners(MIRA, card_id), Some(3));
+		assert_eq!(Balances::reserved_balance(MIRA), 0);
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE - 110);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 110);
+	})
+}
+
+#[test]
+fn auction_royalty_of_reaped_creator_goes_to_seller(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		assert_ok!(Cards::create_card_pack(creator.clone(), new_card(), 10, 50, None));
 		let card_id = Cards::previous_card_id();
+		for _ in 0..3 {
+			assert_ok!(Cards::transfer(creator.clone(), card_id, BOB));
+		}
+
+		assert_ok!(Cards::start_auction(Origin::signed(BOB), card_id, 3, 100, 10, 5));
+		assert_ok!(Cards::bid(Origin::signed(MIRA), Cards::next_auction_id() - 1, 200));
+		// the creator's account is reaped
+		assert_ok!(Balances::transfer(creator, DAVE, INITIAL_BALANCE));
+
+		run_to_block(6);
+		assert_eq!(Cards::owners(MIRA, card_id), Some(3));
+		assert_eq!(Cards::owners(BOB, card_id), None);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE + 200);
+		assert_eq!(Balances::total_balance(&ALICE), 0);
+		assert_eq!(Balances::reserved_balance(MIRA), 0);
+	})
+}
+
+#[test]
+fn failed_auction_settlement_refunds_bid(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		assert_ok!(Cards::set_market_fee(Origin::root(), 20));
+
+		assert_ok!(Cards::start_auction(creator.clone(), card_id, 3, 100, 10, 5));
+		let auction_id = Cards::next_auction_id() - 1;
+		assert_ok!(Cards::bid(Origin::signed(BOB), auction_id, 100));
+		// the fee destination can not receive the fee anymore
+		assert_ok!(Balances::transfer(Origin::signed(TREASURY), DAVE, INITIAL_BALANCE));
+
+		run_to_block(6);
+		assert_eq!(Cards::auctions(auction_id), None);
+		assert_eq!(System::events().last().map(|record| record.event.clone()),
+			Some(Event::pallet(crate::Event::AuctionSettled(auction_id, None))));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(10));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_eq!(Cards::owners(BOB, card_id), None);
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE);
+	})
+}
+
+#[test]
+fn auction_without_bids_returns_cards_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
 
-		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100));
-		assert_eq!(Cards::cards_for_sale(card_id, ALICE), Some(100));
+		assert_ok!(Cards::start_auction(creator.clone(), card_id, 10, 100, 10, 5));
+		let auction_id = Cards::next_auction_id() - 1;
+		assert_noop!(Cards::transfer(creator.clone(), card_id, BOB), Error::<Test>::CardsReserved);
 
+		run_to_block(6);
+		assert_eq!(Cards::auctions(auction_id), None);
+		assert_noop!(Cards::bid(Origin::signed(BOB), auction_id, 100), Error::<Test>::AuctionNotFound);
+		assert_eq!(Cards::owners(ALICE, card_id), Some(10));
 		assert_ok!(Cards::transfer(creator, card_id, BOB));
+	})
+}
+
+#[test]
+fn auction_settlement_is_bounded_per_block(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		for _ in 0..3 {
+			assert_ok!(Cards::start_auction(creator.clone(), card_id, 1, 100, 10, 5));
+		}
+		let last = Cards::next_auction_id() - 1;
+		assert_ok!(Cards::bid(Origin::signed(BOB), last, 100));
+
+		run_to_block(6);
+		assert_eq!(Cards::auctions_ending_at(6), vec![]);
+		assert_eq!(Cards::auctions_ending_at(7), vec![last]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 1);
+		// a carried over auction takes no more bids
+		assert_noop!(Cards::bid(Origin::signed(MIRA), last, 200), Error::<Test>::AuctionEnded);
+
+		run_to_block(7);
+		assert_eq!(Cards::auctions(last), None);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_eq!(Cards::owners(BOB, card_id), Some(1));
+	})
+}
+
+#[test]
+fn cancel_auction_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::start_auction(creator.clone(), card_id, 2, 100, 10, 5));
+		let auction_id = Cards::next_auction_id() - 1;
+
+		assert_noop!(Cards::cancel_auction(Origin::signed(BOB), auction_id), Error::<Test>::NoPermission);
+		assert_ok!(Cards::cancel_auction(creator.clone(), auction_id));
+		assert_eq!(Cards::auctions(auction_id), None);
+		assert_eq!(Cards::auctions_ending_at(6), vec![]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+	})
+}
+
+#[test]
+fn start_auction_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_noop!(Cards::start_auction(creator.clone(), card_id, 2, 100, 10, 1), Error::<Test>::InvalidAuctionDuration);
+		assert_noop!(Cards::start_auction(creator.clone(), card_id, 2, 100, 10, 101), Error::<Test>::InvalidAuctionDuration);
+		assert_noop!(Cards::start_auction(creator.clone(), card_id, 11, 100, 10, 5), Error::<Test>::NotEnoughCards);
+		assert_noop!(Cards::start_auction(Origin::signed(BOB), card_id, 1, 100, 10, 5), Error::<Test>::CardNotOwned);
+
+		assert_ok!(Cards::start_auction(creator.clone(), card_id, 2, 100, 10, 5));
+		let auction_id = Cards::next_auction_id() - 1;
+		assert_noop!(Cards::bid(creator.clone(), auction_id, 100), Error::<Test>::CannotBuyOwnCard);
+		assert_noop!(Cards::bid(Origin::signed(BOB), auction_id, INITIAL_BALANCE + 1), Error::<Test>::InsufficientBalance);
+	})
+}
+
+#[test]
+fn dutch_offer_price_falls_linearly(){
+	let offer = DutchOffer { start_price: 200u64, end_price: 100, start: 10u64, end: 20, amount: 1 };
+	assert_eq!(offer.price_at(5), 200);
+	assert_eq!(offer.price_at(10), 200);
+	assert_eq!(offer.price_at(15), 150);
+	assert_eq!(offer.price_at(19), 110);
+	assert_eq!(offer.price_at(20), 100);
+	assert_eq!(offer.price_at(100), 100);
+}
+
+#[test]
+fn buy_dutch_at_current_price_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let buyer = Origin::signed(BOB);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::set_card_for_dutch_sale(creator.clone(), card_id, 200, 100, 1, 11, 3));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 3);
+
+		run_to_block(6);
+		assert_noop!(Cards::buy_dutch(buyer.clone(), card_id, ALICE, 2, 149), Error::<Test>::PriceAboveMaximum);
+		assert_ok!(Cards::buy_dutch(buyer.clone(), card_id, ALICE, 2, 150));
+		assert_eq!(Cards::owners(BOB, card_id), Some(2));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 300);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 300);
+		assert_eq!(Cards::dutch_sales(card_id, ALICE).map(|offer| offer.amount), Some(1));
+
+		run_to_block(20);
+		assert_ok!(Cards::buy_dutch(Origin::signed(MIRA), card_id, ALICE, 1, 100));
+		assert_eq!(Cards::owners(MIRA, card_id), Some(1));
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE - 100);
+		assert_eq!(Cards::dutch_sales(card_id, ALICE), None);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+	})
+}
+
+#[test]
+fn dutch_sale_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let buyer = Origin::signed(BOB);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_noop!(Cards::set_card_for_dutch_sale(creator.clone(), card_id, 100, 200, 1, 11, 1), Error::<Test>::InvalidDutchSale);
+		assert_noop!(Cards::set_card_for_dutch_sale(creator.clone(), card_id, 200, 100, 11, 11, 1), Error::<Test>::InvalidDutchSale);
+		assert_noop!(Cards::set_card_for_dutch_sale(buyer.clone(), card_id, 200, 100, 1, 11, 1), Error::<Test>::CardNotOwned);
+
+		assert_ok!(Cards::set_card_for_dutch_sale(creator.clone(), card_id, 200, 100, 5, 15, 1));
+		assert_noop!(Cards::buy_dutch(buyer.clone(), card_id, ALICE, 1, 200), Error::<Test>::SaleNotStarted);
+		assert_noop!(Cards::buy_dutch(buyer.clone(), card_id, ALICE, 2, 200), Error::<Test>::NotEnoughCardsForSale);
+
+		assert_ok!(Cards::remove_card_from_dutch_sale(creator.clone(), card_id));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_noop!(Cards::buy_dutch(buyer.clone(), card_id, ALICE, 1, 200), Error::<Test>::CardNotForSale);
+	})
+}
+
+fn commitment(bidder: AccountId, value: u64, salt: &[u8]) -> <Test as frame_system::Config>::Hash {
+	<Test as frame_system::Config>::Hashing::hash_of(&(bidder, value, salt.to_vec()))
+}
+
+#[test]
+fn sealed_auction_second_price_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::start_sealed_auction(creator.clone(), card_id, 2, 100, 5, 5));
+		let auction_id = Cards::next_auction_id() - 1;
+
+		assert_ok!(Cards::commit_bid(Origin::signed(BOB), auction_id, commitment(BOB, 150, b"bob")));
+		assert_ok!(Cards::commit_bid(Origin::signed(MIRA), auction_id, commitment(MIRA, 120, b"mira")));
+		assert_ok!(Cards::commit_bid(Origin::signed(DAVE), auction_id, commitment(DAVE, 500, b"dave")));
+		assert_eq!(Balances::reserved_balance(BOB), 10);
+		assert_noop!(Cards::commit_bid(Origin::signed(BOB), auction_id, commitment(BOB, 1, b"bob")),
+			Error::<Test>::BidAlreadyCommitted);
+		assert_noop!(Cards::reveal_bid(Origin::signed(BOB), auction_id, 150, b"bob".to_vec()),
+			Error::<Test>::NotRevealPhase);
+
+		run_to_block(6);
+		assert_noop!(Cards::commit_bid(Origin::signed(BOB), auction_id, commitment(BOB, 1, b"bob")),
+			Error::<Test>::AuctionEnded);
+		assert_noop!(Cards::reveal_bid(Origin::signed(BOB), auction_id, 160, b"bob".to_vec()),
+			Error::<Test>::InvalidReveal);
+		assert_ok!(Cards::reveal_bid(Origin::signed(BOB), auction_id, 150, b"bob".to_vec()));
+		assert_ok!(Cards::reveal_bid(Origin::signed(MIRA), auction_id, 120, b"mira".to_vec()));
+		assert_eq!(Balances::reserved_balance(BOB), 160);
+		assert_eq!(Balances::reserved_balance(MIRA), 10);
+
+		run_to_block(11);
+		assert_eq!(Cards::sealed_auctions(auction_id), None);
+		assert_eq!(Cards::sealed_bids(auction_id, BOB), None);
+		assert_eq!(Cards::owners(BOB, card_id), Some(2));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(8));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		// winner pays the second best bid
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 120);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 120);
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE);
+		// unrevealed deposit is slashed
+		assert_eq!(Balances::reserved_balance(DAVE), 0);
+		assert_eq!(Balances::free_balance(DAVE), INITIAL_BALANCE - 10);
+	})
+}
+
+#[test]
+fn sealed_auction_single_bid_pays_reserve_price_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::start_sealed_auction(creator.clone(), card_id, 1, 100, 5, 5));
+		let auction_id = Cards::next_auction_id() - 1;
+
+		assert_ok!(Cards::commit_bid(Origin::signed(BOB), auction_id, commitment(BOB, 300, b"bob")));
+		assert_ok!(Cards::commit_bid(Origin::signed(MIRA), auction_id, commitment(MIRA, 50, b"mira")));
+
+		run_to_block(6);
+		assert_ok!(Cards::reveal_bid(Origin::signed(BOB), auction_id, 300, b"bob".to_vec()));
+		// below the reserve price, can not win
+		assert_ok!(Cards::reveal_bid(Origin::signed(MIRA), auction_id, 50, b"mira".to_vec()));
+
+		run_to_block(11);
+		assert_eq!(Cards::owners(BOB, card_id), Some(1));
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 100);
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 100);
+	})
+}
+
+#[test]
+fn sell_to_buy_order_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		assert_ok!(Cards::transfer(creator.clone(), card_id, MIRA));
 
-		assert_noop!(Cards::buy(buyer.clone(), card_id, ALICE), Error::<Test>::CardNotOwned);
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 50, 4, None));
+		let order_id = last_order_id();
+		assert_eq!(Cards::bids(card_id), vec![(50, order_id)]);
+		assert_eq!(Balances::reserved_balance(BOB), 200);
+
+		assert_ok!(Cards::sell_to_order(creator.clone(), order_id, 3));
+		assert_eq!(Cards::owners(BOB, card_id), Some(3));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(6));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 150);
+		assert_eq!(Balances::reserved_balance(BOB), 50);
+		assert_eq!(Cards::orders(order_id).map(|order| order.amount), Some(1));
+
+		assert_noop!(Cards::sell_to_order(Origin::signed(MIRA), order_id, 2), Error::<Test>::NotEnoughCardsWanted);
+		assert_ok!(Cards::sell_to_order(Origin::signed(MIRA), order_id, 1));
+		assert_eq!(Cards::owners(BOB, card_id), Some(4));
+		assert_eq!(Cards::owners(MIRA, card_id), None);
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE + 50);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 200);
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_eq!(Cards::orders(order_id), None);
+		assert_eq!(Cards::bids(card_id), vec![]);
+	})
+}
+
+#[test]
+fn buy_order_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let buyer = Origin::signed(BOB);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_noop!(Cards::place_buy_order(buyer.clone(), card_id + 1, 50, 1, None), Error::<Test>::UnknownCard);
+		assert_noop!(Cards::place_buy_order(buyer.clone(), card_id, 50, 0, None), Error::<Test>::ZeroAmount);
+		assert_noop!(Cards::place_buy_order(buyer.clone(), card_id, 50, 1, Some(1)), Error::<Test>::InvalidExpiry);
+		assert_noop!(Cards::place_buy_order(buyer.clone(), card_id, 600, 2, None), Error::<Test>::InsufficientBalance);
+
+		assert_ok!(Cards::place_buy_order(buyer.clone(), card_id, 50, 2, None));
+		let order_id = last_order_id();
+		assert_noop!(Cards::sell_to_order(buyer.clone(), order_id, 1), Error::<Test>::CannotBuyOwnCard);
+		assert_noop!(Cards::sell_to_order(Origin::signed(MIRA), order_id, 1), Error::<Test>::CardNotOwned);
+		assert_noop!(Cards::cancel_order(creator.clone(), order_id), Error::<Test>::NoPermission);
+
+		assert_ok!(Cards::cancel_order(buyer.clone(), order_id));
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_noop!(Cards::sell_to_order(creator, order_id, 1), Error::<Test>::OrderNotFound);
+	})
+}
+
+#[test]
+fn buy_order_expires_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 50, 2, Some(5)));
+		let order_id = last_order_id();
+		assert_eq!(Cards::orders_expiring_at(5), vec![order_id]);
+
+		run_to_block(4);
+		assert_ok!(Cards::sell_to_order(creator.clone(), order_id, 1));
+
+		run_to_block(5);
+		assert_eq!(Cards::orders(order_id), None);
+		assert_eq!(Cards::bids(card_id), vec![]);
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 50);
+		assert_noop!(Cards::sell_to_order(creator, order_id, 1), Error::<Test>::OrderNotFound);
 	})
 }
 
+#[test]
+fn carried_over_buy_order_can_not_be_filled(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
 
+		for price in 50..53 {
+			assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, price, 1, Some(5)));
+		}
+		run_to_block(5);
+		let order_id = Cards::orders_expiring_at(6)[0];
+		assert_noop!(Cards::sell_to_order(creator.clone(), order_id, 1), Error::<Test>::OrderExpired);
+		// nor matched by a crossing sell order, which rests in the book instead
+		assert_ok!(Cards::set_card_for_sale_with_price(creator, card_id, 40, 1, None));
+		assert_eq!(Cards::asks(card_id), vec![(40, last_order_id())]);
+		assert_eq!(Cards::owners(BOB, card_id), None);
+	})
+}
 
+#[test]
+fn sell_order_expiry_is_bounded_per_block(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		for price in 100..103 {
+			assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, price, 1, Some(5)));
+		}
+		let last = last_order_id();
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 3);
+
+		// at most `MaxExpiriesPerBlock` orders expire, the rest waits for the next block
+		run_to_block(5);
+		assert_eq!(Cards::orders_expiring_at(5), vec![]);
+		assert_eq!(Cards::orders_expiring_at(6), vec![last]);
+		assert_eq!(Cards::asks(card_id), vec![(102, last)]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 1);
+		// the carried over order can not be filled any more
+		assert_noop!(Cards::buy(Origin::signed(BOB), last, 1, 1_000), Error::<Test>::OrderExpired);
+		assert_noop!(Cards::market_buy(Origin::signed(BOB), card_id, 1, 1_000), Error::<Test>::NotEnoughCardsForSale);
+
+		run_to_block(6);
+		assert_eq!(Cards::orders(last), None);
+		assert_eq!(Cards::asks(card_id), vec![]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		let removed = System::events().into_iter()
+			.filter(|record| record.event == Event::pallet(crate::Event::CardRemovedFromSale(card_id, ALICE)))
+			.count();
+		assert_eq!(removed, 3);
+		assert_noop!(Cards::buy(Origin::signed(BOB), last, 1, 1_000), Error::<Test>::OrderNotFound);
+	})
+}
+
+#[test]
+fn order_book_keeps_price_time_priority(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 120, 1, None));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 120, 1, None));
+		assert_eq!(Cards::asks(card_id), vec![(100, 1), (120, 0), (120, 2)]);
+
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 40, 1, None));
+		assert_ok!(Cards::place_buy_order(Origin::signed(MIRA), card_id, 60, 1, None));
+		assert_ok!(Cards::place_buy_order(Origin::signed(DAVE), card_id, 40, 1, None));
+		assert_eq!(Cards::bids(card_id), vec![(60, 4), (40, 3), (40, 5)]);
+	})
+}
+
+#[test]
+fn crossing_bid_fills_cheapest_asks_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		assert_ok!(Cards::transfer(creator.clone(), card_id, MIRA));
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 2, None));
+		let alice_order = last_order_id();
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 120, 1, None));
+		let alice_expensive_order = last_order_id();
+		assert_ok!(Cards::set_card_for_sale_with_price(Origin::signed(MIRA), card_id, 80, 1, None));
+		let mira_order = last_order_id();
+
+		// takes 1 from MIRA at 80, 2 from ALICE at 100 and rests 1 at 110
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 110, 4, None));
+		let bob_order = last_order_id();
+		assert_eq!(Cards::owners(BOB, card_id), Some(3));
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE + 80);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 200);
+		assert_eq!(Balances::reserved_balance(BOB), 110);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 280 - 110);
+
+		assert_eq!(Cards::orders(mira_order), None);
+		assert_eq!(Cards::orders(alice_order), None);
+		assert_eq!(Cards::asks(card_id), vec![(120, alice_expensive_order)]);
+		assert_eq!(Cards::bids(card_id), vec![(110, bob_order)]);
+	})
+}
+
+#[test]
+fn crossing_ask_fills_best_bids_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 50, 2, None));
+		let bob_order = last_order_id();
+		assert_ok!(Cards::place_buy_order(Origin::signed(MIRA), card_id, 70, 1, None));
+
+		// sells 1 to MIRA at 70 and 2 to BOB at 50
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 45, 4, None));
+		let alice_order = last_order_id();
+		assert_eq!(Cards::owners(MIRA, card_id), Some(1));
+		assert_eq!(Cards::owners(BOB, card_id), Some(2));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(7));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 170);
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_eq!(Balances::reserved_balance(MIRA), 0);
+
+		assert_eq!(Cards::orders(bob_order), None);
+		assert_eq!(Cards::bids(card_id), vec![]);
+		assert_eq!(Cards::asks(card_id), vec![(45, alice_order)]);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 1);
+	})
+}
+
+#[test]
+fn crossing_is_bounded_by_max_fills(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		for _ in 0..MaxFillsPerCall::get() + 1 {
+			assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 10, 1, None));
+		}
+		let last_ask = last_order_id();
+
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 10, 10, None));
+		let bob_order = last_order_id();
+		assert_eq!(Cards::owners(BOB, card_id), Some(MaxFillsPerCall::get() as u16));
+		assert_eq!(Cards::asks(card_id), vec![(10, last_ask)]);
+		assert_eq!(Cards::orders(bob_order).map(|order| order.amount), Some(10 - MaxFillsPerCall::get() as u16));
+	})
+}
+
+#[test]
+fn market_buy_sweeps_cheapest_asks_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		assert_ok!(Cards::transfer(creator.clone(), card_id, MIRA));
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 30, 2, None));
+		let alice_order = last_order_id();
+		assert_ok!(Cards::set_card_for_sale_with_price(Origin::signed(MIRA), card_id, 20, 1, None));
+
+		assert_noop!(Cards::market_buy(Origin::signed(BOB), card_id, 2, 49), Error::<Test>::PriceAboveMaximum);
+		assert_ok!(Cards::market_buy(Origin::signed(BOB), card_id, 2, 50));
+		assert_eq!(Cards::owners(BOB, card_id), Some(2));
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 50);
+		assert_eq!(Cards::asks(card_id), vec![(30, alice_order)]);
+
+		// fills what is left in the book
+		assert_ok!(Cards::market_buy(Origin::signed(BOB), card_id, 5, 1_000));
+		assert_eq!(Cards::owners(BOB, card_id), Some(3));
+		assert_eq!(Cards::asks(card_id), vec![]);
+		assert_noop!(Cards::market_buy(Origin::signed(BOB), card_id, 1, 1_000), Error::<Test>::NotEnoughCardsForSale);
+	})
+}
+
+#[test]
+fn order_book_full_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		for _ in 0..MaxOrdersPerBook::get() {
+			assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 10, 1, None));
+		}
+		assert_noop!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 10, 1, None), Error::<Test>::OrderBookFull);
+	})
+}
+
+#[test]
+fn better_order_evicts_worst_from_full_book(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_noop!(Cards::place_buy_order(Origin::signed(MIRA), card_id, 0, 1, None), Error::<Test>::ZeroPrice);
+		assert_noop!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 0, 1, None), Error::<Test>::ZeroPrice);
+
+		for _ in 0..MaxOrdersPerBook::get() {
+			assert_ok!(Cards::place_buy_order(Origin::signed(MIRA), card_id, 1, 1, None));
+		}
+		let worst = last_order_id();
+		assert_eq!(Balances::reserved_balance(MIRA), MaxOrdersPerBook::get() as u64);
+		assert_noop!(Cards::place_buy_order(Origin::signed(BOB), card_id, 1, 1, None), Error::<Test>::OrderBookFull);
+
+		// the youngest of the cheapest bids makes room for a better one
+		assert_ok!(Cards::place_buy_order(Origin::signed(BOB), card_id, 2, 1, None));
+		let best = last_order_id();
+		assert_eq!(Cards::orders(worst), None);
+		assert_eq!(Cards::bids(card_id).len() as u32, MaxOrdersPerBook::get());
+		assert_eq!(Cards::bids(card_id)[0], (2, best));
+		assert_eq!(Balances::reserved_balance(MIRA), MaxOrdersPerBook::get() as u64 - 1);
+	})
+}
+
+#[test]
+fn sell_order_leaving_the_book_is_removed_from_sale(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		let removed = || System::events().into_iter()
+			.filter(|record| record.event == Event::pallet(crate::Event::CardRemovedFromSale(card_id, ALICE)))
+			.count();
+
+		// cancelled
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		assert_ok!(Cards::cancel_order(creator.clone(), last_order_id()));
+		assert_eq!(removed(), 1);
+
+		// sold out
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		assert_ok!(Cards::buy(Origin::signed(BOB), last_order_id(), 1, 100));
+		assert_eq!(removed(), 2);
+
+		// evicted by a cheaper ask
+		for _ in 0..MaxOrdersPerBook::get() {
+			assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		}
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 90, 1, None));
+		assert_eq!(removed(), 3);
+	})
+}
+
+#[test]
+fn accept_trade_swaps_everything_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_a = create_card_pack(creator.clone());
+		let card_b = create_card_pack(creator.clone());
+		assert_ok!(Cards::transfer(creator.clone(), card_b, BOB));
+		assert_ok!(Cards::transfer(creator.clone(), card_b, BOB));
+
+		assert_ok!(Cards::propose_trade(creator.clone(), BOB, vec![(card_a, 3)], vec![(card_b, 2)], 20, 50, None));
+		let trade_id = Cards::next_trade_id() - 1;
+		assert_eq!(Cards::reserved_cards(ALICE, card_a), 3);
+		assert_eq!(Balances::reserved_balance(ALICE), 20 + TradeDeposit::get());
+
+		assert_noop!(Cards::accept_trade(Origin::signed(MIRA), trade_id), Error::<Test>::NoPermission);
+		assert_ok!(Cards::accept_trade(Origin::signed(BOB), trade_id));
+		assert_eq!(Cards::trades(trade_id), None);
+		assert_eq!(Cards::owners(BOB, card_a), Some(3));
+		assert_eq!(Cards::owners(BOB, card_b), None);
+		assert_eq!(Cards::owners(ALICE, card_a), Some(7));
+		assert_eq!(Cards::owners(ALICE, card_b), Some(10));
+		assert_eq!(Cards::reserved_cards(ALICE, card_a), 0);
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE - 20 + 50);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE + 20 - 50);
+	})
+}
+
+#[test]
+fn accept_trade_moves_nothing_on_failure(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_a = create_card_pack(creator.clone());
+		let card_b = create_card_pack(creator.clone());
+		assert_ok!(Cards::transfer(creator.clone(), card_b, BOB));
+
+		assert_ok!(Cards::propose_trade(creator.clone(), BOB, vec![(card_a, 3)], vec![(card_b, 2)], 0, 0, None));
+		let trade_id = Cards::next_trade_id() - 1;
+
+		assert_noop!(Cards::accept_trade(Origin::signed(BOB), trade_id), Error::<Test>::NotEnoughCards);
+		assert_eq!(Cards::owners(BOB, card_a), None);
+		assert_eq!(Cards::reserved_cards(ALICE, card_a), 3);
+
+		// offered copies are locked while the offer is open
+		assert_ok!(Cards::transfer(creator.clone(), card_b, BOB));
+		assert_noop!(Cards::set_card_for_sale_with_price(creator.clone(), card_a, 10, 8, None), Error::<Test>::CardsReserved);
+		assert_ok!(Cards::accept_trade(Origin::signed(BOB), trade_id));
+		assert_eq!(Cards::owners(BOB, card_a), Some(3));
+	})
+}
+
+#[test]
+fn cancel_and_expire_trade_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::propose_trade(creator.clone(), BOB, vec![(card_id, 2)], vec![], 0, 100, None));
+		let trade_id = Cards::next_trade_id() - 1;
+		assert_noop!(Cards::cancel_trade(Origin::signed(MIRA), trade_id), Error::<Test>::NoPermission);
+		// counterparty declines
+		assert_ok!(Cards::cancel_trade(Origin::signed(BOB), trade_id));
+		assert_eq!(Cards::trades(trade_id), None);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+
+		assert_ok!(Cards::propose_trade(creator.clone(), BOB, vec![(card_id, 2)], vec![], 30, 0, Some(5)));
+		let trade_id = Cards::next_trade_id() - 1;
+		assert_eq!(Cards::trades_expiring_at(5), vec![trade_id]);
+
+		run_to_block(5);
+		assert_eq!(Cards::trades(trade_id), None);
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_noop!(Cards::accept_trade(Origin::signed(BOB), trade_id), Error::<Test>::TradeNotFound);
+	})
+}
+
+#[test]
+fn trade_expiry_is_bounded_per_block(){
+	new_test_ext().execute_with(|| {
+		let proposer = Origin::signed(BOB);
+		let card_id = create_card_pack(Origin::signed(ALICE));
+
+		// offers that only ask for cards still lock the deposit
+		for _ in 0..3 {
+			assert_ok!(Cards::propose_trade(proposer.clone(), ALICE, vec![], vec![(card_id, 1)], 0, 0, Some(5)));
+		}
+		let last = Cards::next_trade_id() - 1;
+		assert_eq!(Balances::reserved_balance(BOB), 3 * TradeDeposit::get());
+
+		run_to_block(5);
+		assert_eq!(Cards::trades_expiring_at(5), vec![]);
+		assert_eq!(Cards::trades_expiring_at(6), vec![last]);
+		assert_eq!(Balances::reserved_balance(BOB), TradeDeposit::get());
+		assert_noop!(Cards::accept_trade(Origin::signed(ALICE), last), Error::<Test>::TradeExpired);
+
+		run_to_block(6);
+		assert_eq!(Cards::trades(last), None);
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+	})
+}
+
+#[test]
+fn propose_trade_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_noop!(Cards::propose_trade(creator.clone(), ALICE, vec![(card_id, 1)], vec![], 0, 0, None),
+			Error::<Test>::CannotTradeWithSelf);
+		assert_noop!(Cards::propose_trade(creator.clone(), BOB, vec![], vec![], 0, 0, None),
+			Error::<Test>::EmptyTrade);
+		assert_noop!(Cards::propose_trade(creator.clone(), BOB, vec![(card_id, 1), (card_id, 1)], vec![], 0, 0, None),
+			Error::<Test>::DuplicateTradeItem);
+		assert_noop!(Cards::propose_trade(creator.clone(), BOB, vec![(card_id, 0)], vec![], 0, 0, None),
+			Error::<Test>::ZeroAmount);
+		assert_noop!(Cards::propose_trade(creator.clone(), BOB, vec![], vec![(1, 1), (2, 1), (3, 1), (4, 1)], 0, 0, None),
+			Error::<Test>::TooManyTradeItems);
+		assert_noop!(Cards::propose_trade(creator.clone(), BOB, vec![(card_id, 11)], vec![], 0, 0, None),
+			Error::<Test>::NotEnoughCards);
+		assert_noop!(Cards::propose_trade(creator.clone(), BOB, vec![(card_id, 1)], vec![], INITIAL_BALANCE + 1, 0, None),
+			Error::<Test>::InsufficientBalance);
+	})
+}
+
+#[test]
+fn royalty_paid_on_resale_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		assert_ok!(Cards::create_card_pack(creator.clone(), new_card(), 10, 50, None));
+		let card_id = Cards::previous_card_id();
+		assert_eq!(Cards::royalty(card_id), 50);
+		assert_eq!(Cards::card_info(card_id).map(|info| info.creator), Some(ALICE));
+
+		// no royalty on the creator's own sales
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 2, None));
+		assert_ok!(Cards::buy(Origin::signed(BOB), last_order_id(), 2, 100));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 200);
+
+		// resale paid from free balance
+		assert_ok!(Cards::set_card_for_sale_with_price(Origin::signed(BOB), card_id, 200, 1, None));
+		assert_ok!(Cards::buy(Origin::signed(MIRA), last_order_id(), 1, 200));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 210);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 200 + 190);
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE - 200);
+
+		// resale paid from a reserved bid
+		assert_ok!(Cards::place_buy_order(Origin::signed(DAVE), card_id, 100, 1, None));
+		assert_ok!(Cards::sell_to_order(Origin::signed(BOB), last_order_id(), 1));
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 215);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 200 + 190 + 95);
+		assert_eq!(Balances::free_balance(DAVE), INITIAL_BALANCE - 100);
+		assert_eq!(Balances::reserved_balance(DAVE), 0);
+	})
+}
+
+#[test]
+fn royalty_too_high_error(){
+	new_test_ext().execute_with(|| {
+		assert_noop!(Cards::create_card_pack(Origin::signed(ALICE), new_card(), 10, 201, None),
+			Error::<Test>::RoyaltyTooHigh);
+	})
+}
+
+#[test]
+fn market_fee_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		assert_ok!(Cards::create_card_pack(creator.clone(), new_card(), 10, 50, None));
+		let card_id = Cards::previous_card_id();
+		assert_eq!(Cards::market_fee(), 0);
+
+		assert_noop!(Cards::set_market_fee(Origin::signed(ALICE), 20), sp_runtime::DispatchError::BadOrigin);
+		assert_noop!(Cards::set_market_fee(Origin::root(), 1_001), Error::<Test>::InvalidFee);
+		assert_ok!(Cards::set_market_fee(Origin::root(), 20));
+		assert_eq!(Cards::market_fee(), 20);
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 1, None));
+		assert_ok!(Cards::buy(Origin::signed(BOB), last_order_id(), 1, 100));
+		assert_eq!(Balances::free_balance(TREASURY), INITIAL_BALANCE + 2);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 98);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 100);
+
+		// the fee comes before the royalty and the seller
+		assert_ok!(Cards::place_buy_order(Origin::signed(MIRA), card_id, 200, 1, None));
+		assert_ok!(Cards::sell_to_order(Origin::signed(BOB), last_order_id(), 1));
+		assert_eq!(Balances::free_balance(TREASURY), INITIAL_BALANCE + 6);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 108);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 100 + 186);
+		assert_eq!(Balances::free_balance(MIRA), INITIAL_BALANCE - 200);
+		assert_eq!(Balances::reserved_balance(MIRA), 0);
+	})
+}
+
+#[test]
+fn market_stats_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		assert_eq!(Cards::market_stats(card_id), None);
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 100, 2, None));
+		assert_ok!(Cards::buy(Origin::signed(BOB), last_order_id(), 2, 100));
+		run_to_block(5);
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 80, 1, None));
+		assert_ok!(Cards::buy(Origin::signed(MIRA), last_order_id(), 1, 80));
+
+		assert_eq!(Cards::market_stats(card_id), Some(MarketStats {
+			last_price: 80, last_sale: 5, high: 100, low: 80, volume: 280, copies_sold: 3,
+		}));
+		assert_eq!(Cards::rolling_volume(card_id), 280);
+
+		// sales older than `VolumeWindow` drop out of the rolling volume
+		run_to_block(12);
+		assert_eq!(Cards::rolling_volume(card_id), 80);
+
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 150, 2, None));
+		let order_id = last_order_id();
+		assert_ok!(Cards::buy(Origin::signed(DAVE), order_id, 1, 150));
+		assert_ok!(Cards::buy(Origin::signed(DAVE), order_id, 1, 150));
+		assert_eq!(Cards::rolling_volume(card_id), 380);
+		// buckets of two blocks, in slot `block / 2 % VolumeBuckets`
+		assert_eq!(Cards::volume_history(card_id, 2), (4, 80));
+		assert_eq!(Cards::volume_history(card_id, 1), (12, 300));
+
+		// only the latest `MaxRecentTrades` sales are kept
+		let trades = Cards::recent_trades(card_id);
+		assert_eq!(trades.len(), 3);
+		assert_eq!(trades[0], SaleRecord { seller: ALICE, buyer: MIRA, amount: 1, price: 80, block: 5 });
+		assert_eq!(trades[2], SaleRecord { seller: ALICE, buyer: DAVE, amount: 1, price: 150, block: 12 });
+		assert_eq!(Cards::market_stats(card_id).map(|stats| (stats.high, stats.volume)), Some((150, 580)));
+
+		// a bucket a window later takes the slot over
+		run_to_block(21);
+		assert_ok!(Cards::set_card_for_sale_with_price(creator.clone(), card_id, 50, 1, None));
+		assert_ok!(Cards::buy(Origin::signed(DAVE), last_order_id(), 1, 50));
+		assert_eq!(Cards::volume_history(card_id, 0), (20, 50));
+		assert_eq!(Cards::rolling_volume(card_id), 350);
+	})
+}
+
+#[test]
+fn buy_with_asset_ok(){
+	new_test_ext().execute_with(|| {
+		create_gems();
+		let creator = Origin::signed(ALICE);
+		assert_ok!(Cards::create_card_pack(creator.clone(), new_card(), 10, 50, None));
+		let card_id = Cards::previous_card_id();
+
+		assert_ok!(Cards::set_card_for_asset_sale(creator.clone(), card_id, GEM, 100, 3));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 3);
+		assert_ok!(Cards::buy_with_asset(Origin::signed(BOB), card_id, ALICE, 2, 100));
+		assert_eq!(Cards::owners(BOB, card_id), Some(2));
+		assert_eq!(Cards::asset_listings(card_id, ALICE).map(|listing| listing.amount), Some(1));
+		assert_eq!(Assets::balance(GEM, ALICE), 200);
+		assert_eq!(Assets::balance(GEM, BOB), INITIAL_BALANCE - 200);
+		// native balances are untouched
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE);
+
+		// royalties are paid in the asset of the sale
+		assert_ok!(Cards::set_card_for_asset_sale(Origin::signed(BOB), card_id, GEM, 200, 1));
+		assert_ok!(Cards::buy_with_asset(Origin::signed(MIRA), card_id, BOB, 1, 200));
+		assert_eq!(Assets::balance(GEM, ALICE), 210);
+		assert_eq!(Assets::balance(GEM, BOB), INITIAL_BALANCE - 200 + 190);
+		assert_eq!(Assets::balance(GEM, MIRA), INITIAL_BALANCE - 200);
+		assert_eq!(Cards::asset_listings(card_id, BOB), None);
+
+		assert_ok!(Cards::remove_card_from_asset_sale(creator.clone(), card_id));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 0);
+	})
+}
+
+#[test]
+fn buy_with_asset_error(){
+	new_test_ext().execute_with(|| {
+		create_gems();
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_noop!(Cards::buy_with_asset(Origin::signed(BOB), card_id, ALICE, 1, 100), Error::<Test>::CardNotForSale);
+		assert_ok!(Cards::set_card_for_asset_sale(creator.clone(), card_id, GEM, 100, 2));
+		assert_noop!(Cards::buy_with_asset(creator.clone(), card_id, ALICE, 1, 100), Error::<Test>::CannotBuyOwnCard);
+		assert_noop!(Cards::buy_with_asset(Origin::signed(BOB), card_id, ALICE, 3, 100),
+			Error::<Test>::NotEnoughCardsForSale);
+		assert_noop!(Cards::buy_with_asset(Origin::signed(BOB), card_id, ALICE, 1, 99),
+			Error::<Test>::PriceAboveMaximum);
+		// DAVE holds no gems
+		assert_noop!(Cards::buy_with_asset(Origin::signed(DAVE), card_id, ALICE, 1, 100),
+			Error::<Test>::InsufficientBalance);
+	})
+}
+
+#[test]
+fn rent_card_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_ok!(Cards::offer_for_rent(creator.clone(), card_id, 30, 5, 3));
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 3);
+		assert_ok!(Cards::rent(Origin::signed(BOB), card_id, ALICE, 2, 30));
+		let rental_id = Cards::next_rental_id() - 1;
+		assert_eq!(Cards::rentals_ending_at(6), vec![rental_id]);
+		assert_eq!(Balances::free_balance(ALICE), INITIAL_BALANCE + 60);
+		assert_eq!(Balances::free_balance(BOB), INITIAL_BALANCE - 60);
+
+		// the renter may play the copies but does not own them
+		assert_eq!(Cards::usable_cards(&BOB, card_id), 2);
+		assert_eq!(Cards::usable_cards(&ALICE, card_id), 8);
+		assert_eq!(Cards::owners(BOB, card_id), None);
+		assert_noop!(Cards::transfer(Origin::signed(BOB), card_id, MIRA), Error::<Test>::CardNotOwned);
+		// nor can the owner move them during the term
+		assert_eq!(Cards::reserved_cards(ALICE, card_id), 3);
+		assert_ok!(Cards::remove_rental_offer(creator.clone(), card_id));
+		assert_eq!(Cards::free_cards(&ALICE, card_id), 8);
+
+		run_to_block(6);
+		assert_eq!(Cards::rentals(rental_id), None);
+		assert_eq!(Cards::usable_cards(&BOB, card_id), 0);
+		assert_eq!(Cards::usable_cards(&ALICE, card_id), 10);
+		assert_eq!(Cards::free_cards(&ALICE, card_id), 10);
+	})
+}
+
+#[test]
+fn rent_card_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+
+		assert_noop!(Cards::offer_for_rent(creator.clone(), card_id, 30, 0, 1), Error::<Test>::InvalidRentalDuration);
+		assert_noop!(Cards::offer_for_rent(creator.clone(), card_id, 30, 21, 1), Error::<Test>::InvalidRentalDuration);
+		assert_noop!(Cards::rent(Origin::signed(BOB), card_id, ALICE, 1, 30), Error::<Test>::CardNotForSale);
+
+		assert_ok!(Cards::offer_for_rent(creator.clone(), card_id, 30, 5, 2));
+		assert_noop!(Cards::rent(creator.clone(), card_id, ALICE, 1, 30), Error::<Test>::CannotBuyOwnCard);
+		assert_noop!(Cards::rent(Origin::signed(BOB), card_id, ALICE, 3, 30), Error::<Test>::NotEnoughCardsForSale);
+		assert_noop!(Cards::rent(Origin::signed(BOB), card_id, ALICE, 1, 29), Error::<Test>::PriceAboveMaximum);
+	})
+}
+
+#[test]
+fn color_set_ok(){
+	let rakdos = RED | BLACK;
+	assert_eq!(rakdos.bits(), 6);
+	assert_eq!(rakdos.count(), 2);
+	assert_eq!(rakdos.class(), ColorClass::Multicolor);
+	assert!(rakdos.contains(RED) && !rakdos.contains(RED | GREEN));
+	assert_eq!(rakdos & (RED | WHITE), RED);
+	assert_eq!(RED.class(), ColorClass::Mono);
+	assert_eq!(COLORLESS.class(), ColorClass::Colorless);
+	assert_eq!(Color::from_bits(32), None);
+	assert_eq!(Color::from_bits_truncate(32 | 4), RED);
+}
+
+#[test]
+fn color_keeps_u8_encoding(){
+	use codec::{Decode, Encode};
+
+	assert_eq!((GREEN | BLUE).encode(), vec![24u8]);
+	assert_eq!(Color::decode(&mut &[24u8][..]).ok(), Some(GREEN | BLUE));
+	assert!(Color::decode(&mut &[64u8][..]).is_err());
+}
+
+#[test]
+fn create_card_with_type_line(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let type_line = |supertypes, types, subtypes: Vec<&str>| TypeLine {
+			supertypes,
+			types,
+			subtypes: subtypes.into_iter().map(|subtype| subtype.as_bytes().to_vec()).collect(),
+		};
+		let card = |type_line| Card { type_line, ..new_card() };
+
+		let legendary_artifact_creature = type_line(vec![Supertype::Legendary],
+			vec![CardType::Artifact, CardType::Creature], vec!["Elf"]);
+		assert_ok!(Cards::create_card_pack(creator.clone(), card(legendary_artifact_creature), 1, 0, None));
+		let stored = Cards::cards(Cards::previous_card_id()).unwrap();
+		assert_eq!(stored.type_line.supertypes, vec![Supertype::Legendary]);
+		assert_eq!(stored.type_line.types, vec![CardType::Artifact, CardType::Creature]);
+
+		assert_noop!(Cards::create_card_pack(creator.clone(), card(type_line(vec![], vec![], vec![])), 1, 0, None),
+			Error::<Test>::InvalidTypeLine);
+		assert_noop!(Cards::create_card_pack(creator.clone(),
+			card(type_line(vec![], vec![CardType::Land, CardType::Land], vec![])), 1, 0, None),
+			Error::<Test>::InvalidTypeLine);
+		assert_noop!(Cards::create_card_pack(creator.clone(),
+			card(type_line(vec![], vec![CardType::Land], vec!["Forest", "Forest"])), 1, 0, None),
+			Error::<Test>::InvalidTypeLine);
+		assert_noop!(Cards::create_card_pack(creator.clone(),
+			card(type_line(vec![], vec![CardType::Land], vec![""])), 1, 0, None),
+			Error::<Test>::InvalidTypeLine);
+		assert_noop!(Cards::create_card_pack(creator.clone(),
+			card(type_line(vec![], vec![CardType::Creature], vec!["Elf", "Druid", "Warrior", "Scout"])), 1, 0, None),
+			Error::<Test>::TooManySubtypes);
+		// the count is checked before the subtypes are compared
+		assert_noop!(Cards::create_card_pack(creator.clone(),
+			card(type_line(vec![], vec![CardType::Creature], vec!["Elf"; 4])), 1, 0, None),
+			Error::<Test>::TooManySubtypes);
+	});
+}
+
+#[test]
+fn migrate_card_type_to_type_line(){
+	use codec::Encode;
+	use frame_support::traits::OnRuntimeUpgrade;
+	use crate::migration::{CardV0, Releases};
+
+	new_test_ext().execute_with(|| {
+		let old = CardV0 {
+			name: b"Llanowar Elves".to_vec(),
+			card_type: CardType::Creature,
+			color: GREEN.bits(),
+			rules: b"Add G".to_vec(),
+			image: H256([7u8; 32]),
+		};
+		frame_support::storage::unhashed::put_raw(&crate::CardRegistry::<Test>::hashed_key_for(1), &old.encode());
+		crate::pallet::StorageVersion::<Test>::put(Releases::V0);
+
+		Cards::on_runtime_upgrade();
+		let card = Cards::cards(1).unwrap();
+		assert_eq!(card.name, old.name);
+		assert_eq!(card.type_line.types, vec![CardType::Creature]);
+		assert!(card.type_line.supertypes.is_empty() && card.type_line.subtypes.is_empty());
+		assert_eq!(card.color, GREEN);
+		assert_eq!(card.mana_cost, ManaCost::default());
+		assert_eq!((card.keywords, card.power_toughness, card.loyalty), (Keywords::default(), None, None));
+		assert_eq!(card.rarity, Rarity::Common);
+		assert_eq!(crate::pallet::StorageVersion::<Test>::get(), Releases::LATEST);
+	});
+}
+
+#[test]
+fn migrate_card_with_undefined_color_bits(){
+	use codec::Encode;
+	use frame_support::traits::OnRuntimeUpgrade;
+	use crate::migration::{CardV0, Releases};
+
+	new_test_ext().execute_with(|| {
+		let old = CardV0 {
+			name: b"Shock".to_vec(),
+			card_type: CardType::Instant,
+			color: 0b1110_0100,
+			rules: b"Deal 2 damage".to_vec(),
+			image: H256([9u8; 32]),
+		};
+		frame_support::storage::unhashed::put_raw(&crate::CardRegistry::<Test>::hashed_key_for(1), &old.encode());
+		crate::pallet::StorageVersion::<Test>::put(Releases::V0);
+
+		Cards::on_runtime_upgrade();
+		let card = Cards::cards(1).unwrap();
+		assert_eq!(card.color, RED);
+		assert_eq!(card.type_line.types, vec![CardType::Instant]);
+	});
+}
+
+#[test]
+fn migrate_royalty_creators_to_card_infos(){
+	use codec::Encode;
+	use frame_support::traits::OnRuntimeUpgrade;
+	use crate::migration::{Releases, RoyaltyV4};
+
+	new_test_ext().execute_with(|| {
+		let card_id = create_card_pack(Origin::signed(ALICE));
+		let old = RoyaltyV4 { creator: ALICE, per_mill: 30 };
+		frame_support::storage::unhashed::put_raw(&crate::CardRoyalties::<Test>::hashed_key_for(card_id), &old.encode());
+		// a card created before provenance was recorded
+		let legacy = RoyaltyV4 { creator: BOB, per_mill: 20 };
+		frame_support::storage::unhashed::put_raw(&crate::CardRoyalties::<Test>::hashed_key_for(card_id + 1), &legacy.encode());
+		crate::pallet::StorageVersion::<Test>::put(Releases::V4);
+
+		Cards::on_runtime_upgrade();
+		assert_eq!(Cards::royalty(card_id), 30);
+		assert_eq!(Cards::card_info(card_id).map(|info| (info.creator, info.initial_supply)), Some((ALICE, 10)));
+		assert_eq!(Cards::royalty(card_id + 1), 20);
+		assert_eq!(Cards::card_info(card_id + 1), Some(CardInfo { creator: BOB, created: 0, timestamp: 0, initial_supply: 0 }));
+		assert_eq!(crate::pallet::StorageVersion::<Test>::get(), Releases::LATEST);
+	});
+}
+
+#[test]
+fn migrate_cards_without_mana_cost(){
+	use codec::Encode;
+	use frame_support::traits::OnRuntimeUpgrade;
+	use crate::migration::{CardV1, Releases};
+
+	new_test_ext().execute_with(|| {
+		let old = CardV1 {
+			name: b"Forest".to_vec(),
+			type_line: TypeLine { supertypes: vec![Supertype::Basic], types: vec![CardType::Land], subtypes: vec![b"Forest".to_vec()] },
+			color: COLORLESS,
+			rules: vec![],
+			image: H256([8u8; 32]),
+		};
+		frame_support::storage::unhashed::put_raw(&crate::CardRegistry::<Test>::hashed_key_for(1), &old.encode());
+		crate::pallet::StorageVersion::<Test>::put(Releases::V1);
+
+		Cards::on_runtime_upgrade();
+		let card = Cards::cards(1).unwrap();
+		assert_eq!(card.type_line.supertypes, vec![Supertype::Basic]);
+		assert_eq!(card.type_line.subtypes[0], b"Forest".to_vec());
+		assert_eq!(card.mana_cost, ManaCost::default());
+		assert_eq!(crate::pallet::StorageVersion::<Test>::get(), Releases::LATEST);
+	});
+}
+
+#[test]
+fn mana_cost_ok(){
+	let cost = ManaCost { x: 1, generic: 2, pips: vec![ManaPip::Colored(RED), ManaPip::Hybrid(RED, GREEN)] };
+	assert_eq!(cost.mana_value(), 4);
+	assert_eq!(cost.color_identity(), RED | GREEN);
+	assert!(!ManaPip::Colored(RED | GREEN).is_valid());
+	assert!(!ManaPip::Hybrid(RED, RED).is_valid());
+}
+
+#[test]
+fn create_card_checks_mana_cost(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card = |generic, pips: Vec<ManaPip>, color| Card { mana_cost: ManaCost { x: 0, generic, pips }, color, ..new_card() };
+
+		assert_ok!(Cards::create_card_pack(creator.clone(), card(2, vec![ManaPip::Hybrid(RED, GREEN)], RED | GREEN), 1, 0, None));
+		let stored = Cards::cards(Cards::previous_card_id()).unwrap();
+		assert_eq!(stored.mana_cost.mana_value(), 3);
+		// colorless costs leave the color to the card
+		assert_ok!(Cards::create_card_pack(creator.clone(), card(3, vec![], BLUE), 1, 0, None));
+
+		assert_noop!(Cards::create_card_pack(creator.clone(), card(0, vec![ManaPip::Colored(RED)], RED | BLACK), 1, 0, None),
+			Error::<Test>::ColorMismatch);
+		assert_noop!(Cards::create_card_pack(creator.clone(), card(0, vec![ManaPip::Colored(COLORLESS)], COLORLESS), 1, 0, None),
+			Error::<Test>::InvalidManaPip);
+		assert_noop!(Cards::create_card_pack(creator.clone(), card(0, vec![ManaPip::Colored(RED); 5], RED), 1, 0, None),
+			Error::<Test>::TooManyManaPips);
+	});
+}
+
+#[test]
+fn create_card_checks_stats(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let typed = |card_type| TypeLine { supertypes: vec![], types: vec![card_type], subtypes: vec![] };
+
+		assert_ok!(Cards::create_card_pack(creator.clone(), new_card(), 1, 0, None));
+		let stored = Cards::cards(Cards::previous_card_id()).unwrap();
+		assert!(stored.keywords.contains(Keyword::Haste) && !stored.keywords.contains(Keyword::Flying));
+		assert_eq!(stored.power_toughness, Some(PowerToughness { power: 2, toughness: 2 }));
+
+		let walker = Card { type_line: typed(CardType::Planeswalker), power_toughness: None, loyalty: Some(3), ..new_card() };
+		assert_ok!(Cards::create_card_pack(creator.clone(), walker, 1, 0, None));
+
+		let land_with_stats = Card { type_line: typed(CardType::Land), ..new_card() };
+		assert_noop!(Cards::create_card_pack(creator.clone(), land_with_stats, 1, 0, None), Error::<Test>::InvalidStats);
+		let creature_without_stats = Card { power_toughness: None, ..new_card() };
+		assert_noop!(Cards::create_card_pack(creator.clone(), creature_without_stats, 1, 0, None), Error::<Test>::InvalidStats);
+		let creature_with_loyalty = Card { loyalty: Some(3), ..new_card() };
+		assert_noop!(Cards::create_card_pack(creator.clone(), creature_with_loyalty, 1, 0, None), Error::<Test>::InvalidStats);
+	});
+}
+
+#[test]
+fn keywords_keep_u32_encoding(){
+	use codec::{Decode, Encode};
+
+	let keywords = Keywords::from(&[Keyword::Flying, Keyword::Trample][..]);
+	assert_eq!(keywords.encode(), 3u32.encode());
+	assert_eq!(Keywords::decode(&mut &3u32.encode()[..]).ok(), Some(keywords));
+	assert!(Keywords::decode(&mut &(1u32 << 20).encode()[..]).is_err());
+}
+
+#[test]
+fn pack_amount_limited_by_rarity(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card = |rarity| Card { rarity, ..new_card() };
+
+		assert_ok!(Cards::create_card_pack(creator.clone(), card(Rarity::Mythic), MaxMythicPack::get(), 0, None));
+		let id = Cards::previous_card_id();
+		assert_eq!(Cards::cards(id).map(|card| card.rarity), Some(Rarity::Mythic));
+		assert_eq!(System::events().last().map(|record| record.event.clone()),
+			Some(Event::from(crate::Event::CardCreated(ALICE, id, MaxMythicPack::get(), Rarity::Mythic))));
+
+		assert_noop!(Cards::create_card_pack(creator.clone(), card(Rarity::Mythic), MaxMythicPack::get() + 1, 0, None),
+			Error::<Test>::PackTooLarge);
+		assert_noop!(Cards::create_card_pack(creator.clone(), card(Rarity::Promo), 2, 0, None), Error::<Test>::PackTooLarge);
+		assert_ok!(Cards::create_card_pack(creator.clone(), card(Rarity::Common), MaxCommonPack::get(), 0, None));
+	});
+}
+
+#[test]
+fn card_set_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		assert_ok!(Cards::create_set(creator.clone(), b"DOM".to_vec(), b"Dominaria".to_vec(), 10, 3));
+		let set_id = Cards::next_set_id() - 1;
+		assert_eq!(Cards::set_by_code(b"DOM".to_vec()), Some(set_id));
+
+		assert_ok!(Cards::create_card_in_set(creator.clone(), set_id, 3, new_card(), 10, 0, None));
+		let third = Cards::previous_card_id();
+		assert_ok!(Cards::create_card_in_set(creator.clone(), set_id, 1, new_card(), 10, 0, None));
+		let first = Cards::previous_card_id();
+		assert_eq!(Cards::set_cards(set_id), vec![(1, first), (3, third)]);
+		assert_eq!(Cards::printing(third), Some((set_id, 3)));
+		assert_eq!(Cards::owners(ALICE, third), Some(10));
+
+		assert_noop!(Cards::create_card_in_set(creator.clone(), set_id, 3, new_card(), 10, 0, None),
+			Error::<Test>::CollectorNumberTaken);
+		assert_noop!(Cards::create_card_in_set(creator.clone(), set_id, 4, new_card(), 10, 0, None),
+			Error::<Test>::InvalidCollectorNumber);
+
+		assert_noop!(Cards::close_set(Origin::signed(BOB), set_id), Error::<Test>::NoPermission);
+		assert_ok!(Cards::close_set(creator.clone(), set_id));
+		assert!(Cards::sets(set_id).unwrap().closed);
+		assert_noop!(Cards::create_card_in_set(creator.clone(), set_id, 2, new_card(), 10, 0, None), Error::<Test>::SetClosed);
+	})
+}
+
+#[test]
+fn withdrawn_creator_can_not_add_cards_to_set(){
+	new_test_ext().execute_with(|| {
+		assert_ok!(Cards::set_creator(Origin::signed(ALICE), BOB));
+		assert_ok!(Cards::create_set(Origin::signed(BOB), b"DOM".to_vec(), b"Dominaria".to_vec(), 10, 3));
+		let set_id = Cards::next_set_id() - 1;
+		assert_ok!(Cards::withdraw_creator(Origin::signed(ALICE), BOB));
+
+		assert_noop!(Cards::create_card_in_set(Origin::signed(BOB), set_id, 1, new_card(), 10, 0, None),
+			Error::<Test>::NoPermission);
+		assert_eq!(Cards::set_cards(set_id), vec![]);
+	})
+}
+
+#[test]
+fn create_set_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		assert_noop!(Cards::create_set(Origin::signed(BOB), b"DOM".to_vec(), b"Dominaria".to_vec(), 10, 3),
+			Error::<Test>::NoPermission);
+		assert_noop!(Cards::create_set(creator.clone(), b"".to_vec(), b"Dominaria".to_vec(), 10, 3),
+			Error::<Test>::InvalidSetCode);
+		assert_noop!(Cards::create_set(creator.clone(), b"D-M".to_vec(), b"Dominaria".to_vec(), 10, 3),
+			Error::<Test>::InvalidSetCode);
+		assert_noop!(Cards::create_set(creator.clone(), b"DOMIN".to_vec(), b"Dominaria".to_vec(), 10, 3),
+			Error::<Test>::InvalidSetCode);
+		assert_noop!(Cards::create_set(creator.clone(), b"DOM".to_vec(), b"Dominaria".to_vec(), 10, 0),
+			Error::<Test>::InvalidSetSize);
+		assert_noop!(Cards::create_set(creator.clone(), b"DOM".to_vec(), b"Dominaria".to_vec(), 10, 6),
+			Error::<Test>::InvalidSetSize);
+
+		assert_ok!(Cards::create_set(creator.clone(), b"DOM".to_vec(), b"Dominaria".to_vec(), 10, 3));
+		assert_noop!(Cards::create_set(creator.clone(), b"DOM".to_vec(), b"Dominaria".to_vec(), 10, 3),
+			Error::<Test>::SetCodeTaken);
+		assert_noop!(Cards::create_card_in_set(Origin::signed(BOB), 0, 1, new_card(), 10, 0, None), Error::<Test>::NoPermission);
+		assert_noop!(Cards::create_card_in_set(creator, 1, 1, new_card(), 10, 0, None), Error::<Test>::SetNotFound);
+	})
+}
+
+#[test]
+fn issue_errata_ok(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		let stats = Some(PowerToughness { power: 2, toughness: 1 });
+
+		run_to_block(3);
+		assert_ok!(Cards::issue_errata(creator.clone(), card_id, b"Haste, Trample".to_vec(),
+			Keywords::from(&[Keyword::Haste, Keyword::Trample][..]), stats, None));
+		let card = Cards::cards(card_id).unwrap();
+		assert_eq!(card.rules, b"Haste, Trample".to_vec());
+		assert_eq!(card.power_toughness, stats);
+		let history = Cards::card_history(card_id);
+		assert_eq!(history.len(), 1);
+		assert_eq!(history[0].rules, new_card().rules);
+		assert_eq!(history[0].power_toughness, new_card().power_toughness);
+		assert_eq!(history[0].replaced, 3);
+
+		// governance may issue errata too
+		assert_ok!(Cards::issue_errata(Origin::root(), card_id, b"Haste".to_vec(), Keywords::default(), stats, None));
+		assert_eq!(Cards::card_history(card_id).len(), 2);
+
+		// a full history drops its oldest version, only freezing stops errata
+		assert_ok!(Cards::issue_errata(creator.clone(), card_id, b"Flying".to_vec(), Keywords::default(), stats, None));
+		let history = Cards::card_history(card_id);
+		assert_eq!(history.len(), 2);
+		assert_eq!(history[0].rules, b"Haste, Trample".to_vec());
+		assert_eq!(history[1].rules, b"Haste".to_vec());
+		assert_eq!(System::events().last().map(|record| record.event.clone()),
+			Some(Event::from(crate::Event::CardErrata(card_id, 2))));
+	})
+}
+
+#[test]
+fn issue_errata_error(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		let card_id = create_card_pack(creator.clone());
+		let stats = new_card().power_toughness;
+
+		assert_noop!(Cards::issue_errata(Origin::signed(BOB), card_id, b"Haste".to_vec(), Keywords::default(), stats, None),
+			Error::<Test>::NoPermission);
+		assert_noop!(Cards::issue_errata(creator.clone(), card_id + 1, b"Haste".to_vec(), Keywords::default(), stats, None),
+			Error::<Test>::UnknownCard);
+		assert_noop!(Cards::issue_errata(creator.clone(), card_id, b"Haste".to_vec(), Keywords::default(), None, None),
+			Error::<Test>::InvalidStats);
+		assert_noop!(Cards::issue_errata(creator.clone(), card_id, vec![0x07], Keywords::default(), stats, None),
+			Error::<Test>::InvalidEncoding);
+
+		assert_noop!(Cards::freeze_card(Origin::signed(BOB), card_id), Error::<Test>::NoPermission);
+		assert_ok!(Cards::freeze_card(creator.clone(), card_id));
+		assert!(Cards::is_frozen(card_id));
+		assert_noop!(Cards::issue_errata(Origin::root(), card_id, b"Haste".to_vec(), Keywords::default(), stats, None),
+			Error::<Test>::CardIsFrozen);
+		assert_noop!(Cards::freeze_card(Origin::root(), card_id), Error::<Test>::CardIsFrozen);
+	})
+}
+
+#[test]
+fn card_provenance_recorded(){
+	new_test_ext().execute_with(|| {
+		run_to_block(4);
+		Timestamp::set_timestamp(24_000);
+		let card_id = create_card_pack(Origin::signed(ALICE));
+
+		assert_eq!(Cards::card_info(card_id), Some(CardInfo {
+			creator: ALICE,
+			created: 4,
+			timestamp: 24_000,
+			initial_supply: 10,
+		}));
+		// transfers do not change the provenance
+		assert_ok!(Cards::transfer(Origin::signed(ALICE), card_id, BOB));
+		assert_eq!(Cards::card_info(card_id).map(|info| info.creator), Some(ALICE));
+	})
+}
+
+#[test]
+fn creator_mints_more_copies(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		assert_ok!(Cards::create_card_pack(creator.clone(), new_card(), 10, 0, Some(25)));
+		let card_id = Cards::previous_card_id();
+		assert_eq!(Cards::supply(card_id), Supply { minted: 10, max: Some(25) });
+
+		assert_ok!(Cards::mint_more(creator.clone(), card_id, BOB, 15));
+		assert_eq!(Cards::owners(BOB, card_id), Some(15));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(10));
+		assert_eq!(Cards::supply(card_id).minted, 25);
+		// the initial supply stays what the card was created with
+		assert_eq!(Cards::card_info(card_id).map(|info| info.initial_supply), Some(10));
+
+		assert_noop!(Cards::mint_more(creator.clone(), card_id, BOB, 1), Error::<Test>::SupplyExceeded);
+	})
+}
+
+#[test]
+fn mint_more_checks(){
+	new_test_ext().execute_with(|| {
+		let creator = Origin::signed(ALICE);
+		assert_noop!(Cards::create_card_pack(creator.clone(), new_card(), 10, 0, Some(9)),
+			Error::<Test>::SupplyExceeded);
+		let card_id = create_card_pack(creator.clone());
+		assert_eq!(Cards::supply(card_id).max, None);
+
+		assert_noop!(Cards::mint_more(Origin::signed(BOB), card_id, BOB, 1), Error::<Test>::NoPermission);
+		assert_noop!(Cards::mint_more(creator.clone(), card_id + 1, BOB, 1), Error::<Test>::UnknownCard);
+		assert_noop!(Cards::mint_more(creator.clone(), card_id, BOB, 0), Error::<Test>::ZeroAmount);
+		assert_noop!(Cards::mint_more(creator.clone(), card_id, BOB, MaxCommonPack::get() + 1),
+			Error::<Test>::PackTooLarge);
+		// without a max supply minting is only limited per call
+		assert_ok!(Cards::mint_more(creator.clone(), card_id, ALICE, MaxCommonPack::get()));
+		assert_eq!(Cards::owners(ALICE, card_id), Some(110));
+	})
+}